@@ -0,0 +1,198 @@
+//! Puzzle answers that remember what kind of value they are.
+
+use crate::error::AocError;
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Debug)]
+pub enum Answer {
+    Int(i128),
+    Text(String),
+    /// Multi-line output such as block letters drawn with `#` and `.`.
+    Art(String),
+}
+
+impl Answer {
+    /// Canonical text used for comparisons: surrounding whitespace is
+    /// dropped, and for art each line loses its trailing whitespace.
+    pub fn normalized(&self) -> String {
+        match self {
+            Answer::Int(n) => n.to_string(),
+            Answer::Text(s) => s.trim().to_owned(),
+            Answer::Art(s) => normalize_art(s),
+        }
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Art(_))
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            Answer::Int(n) => match i64::try_from(*n) {
+                Ok(n) => Value::from(n),
+                Err(_) => Value::from(n.to_string()),
+            },
+            Answer::Text(s) => Value::from(s.trim()),
+            Answer::Art(s) => Value::from(normalize_art(s).lines().collect::<Vec<_>>()),
+        }
+    }
+}
+
+fn normalize_art(s: &str) -> String {
+    let lines: Vec<&str> = s.lines().map(str::trim_end).collect();
+    let start = lines
+        .iter()
+        .position(|l| !l.is_empty())
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|l| !l.is_empty())
+        .map_or(start, |i| i + 1);
+    lines[start..end].join("\n")
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.normalized())
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Answer) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl PartialEq<str> for Answer {
+    fn eq(&self, other: &str) -> bool {
+        let other = if other.trim().contains('\n') {
+            normalize_art(other)
+        } else {
+            other.trim().to_owned()
+        };
+        self.normalized() == other
+    }
+}
+
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+macro_rules! int_answer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Int(n as i128)
+                }
+            }
+
+            impl PartialEq<$t> for Answer {
+                fn eq(&self, other: &$t) -> bool {
+                    match self {
+                        Answer::Int(n) => *n == *other as i128,
+                        _ => *self == *format!("{}", other),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+int_answer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, i128);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        if s.trim().contains('\n') {
+            Answer::Art(s)
+        } else {
+            Answer::Text(s)
+        }
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::from(s.to_owned())
+    }
+}
+
+impl From<char> for Answer {
+    fn from(c: char) -> Self {
+        Answer::Text(c.to_string())
+    }
+}
+
+/// Result of a single puzzle part, which may be a plain value or a
+/// `Result` whose error converts into `AocError`.
+pub trait Solution {
+    fn into_answer(self) -> Result<Answer, AocError>;
+}
+
+macro_rules! solution {
+    ($($t:ty),*) => {
+        $(
+            impl Solution for $t {
+                fn into_answer(self) -> Result<Answer, AocError> {
+                    Ok(Answer::from(self))
+                }
+            }
+        )*
+    };
+}
+
+solution!(Answer, u8, u16, u32, u64, usize, i8, i16, i32, i64, i128, String, char);
+
+impl<T, E> Solution for Result<T, E>
+where
+    T: Solution,
+    E: Into<AocError>,
+{
+    fn into_answer(self) -> Result<Answer, AocError> {
+        self.map_err(Into::into)?.into_answer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Answer;
+    use serde_json::json;
+
+    const ART: &str = "
+#..#
+####
+#..#
+
+";
+
+    #[test]
+    fn kinds() {
+        assert!(matches!(Answer::from(42u32), Answer::Int(42)));
+        assert!(matches!(Answer::from("5DB3"), Answer::Text(_)));
+        assert!(Answer::from(ART).is_multiline());
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(Answer::from(1985u32), 1985u32);
+        assert_eq!(Answer::from(1985u32), "1985");
+        assert_eq!(Answer::from(" abc\n"), "abc");
+        assert_eq!(Answer::from(ART), "#..#\n####\n#..#");
+        assert_ne!(Answer::from(7i64), 8i64);
+    }
+
+    #[test]
+    fn rendering() {
+        assert_eq!(Answer::from(ART).to_string(), "#..#\n####\n#..#");
+        assert_eq!(Answer::from(ART).to_json(), json!(["#..#", "####", "#..#"]));
+        assert_eq!(
+            Answer::from(u64::MAX).to_json(),
+            json!("18446744073709551615")
+        );
+        assert_eq!(Answer::from(-3i32).to_json(), json!(-3));
+    }
+}
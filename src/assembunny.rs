@@ -0,0 +1,368 @@
+//! Interpreter for the assembunny language from 2016 days 12, 23 and 25.
+//!
+//! Programs may rewrite themselves with `tgl`, so the peephole optimizer
+//! matches add and multiply loops against the current code each time it
+//! reaches them instead of rewriting the program up front.
+
+use crate::parse::{self, ParseError};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+pub const REGISTERS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Reg(usize),
+    Imm(i64),
+}
+
+impl FromStr for Operand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            &[r @ b'a'..=b'd'] => Ok(Operand::Reg((r - b'a') as usize)),
+            _ => parse::value(s, 1).map(Operand::Imm),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Operand::Reg(r) => write!(f, "{}", (b'a' + r as u8) as char),
+            Operand::Imm(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// An instruction as written. Toggling can produce nonsense such as
+/// `cpy 1 2`, which is kept and skipped when executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instr {
+    Cpy(Operand, Operand),
+    Inc(Operand),
+    Dec(Operand),
+    Jnz(Operand, Operand),
+    Tgl(Operand),
+    Out(Operand),
+}
+
+impl Instr {
+    fn toggled(self) -> Instr {
+        match self {
+            Instr::Inc(x) => Instr::Dec(x),
+            Instr::Dec(x) | Instr::Tgl(x) | Instr::Out(x) => Instr::Inc(x),
+            Instr::Jnz(x, y) => Instr::Cpy(x, y),
+            Instr::Cpy(x, y) => Instr::Jnz(x, y),
+        }
+    }
+}
+
+impl FromStr for Instr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let operand = |word: &str| {
+            // `word` borrows from `s`, so its offset gives the column.
+            let column = word.as_ptr() as usize - s.as_ptr() as usize + 1;
+            word.parse::<Operand>()
+                .map_err(|e| ParseError::new(column, e.message))
+        };
+        match words.as_slice() {
+            ["cpy", x, y] => Ok(Instr::Cpy(operand(x)?, operand(y)?)),
+            ["inc", x] => Ok(Instr::Inc(operand(x)?)),
+            ["dec", x] => Ok(Instr::Dec(operand(x)?)),
+            ["jnz", x, y] => Ok(Instr::Jnz(operand(x)?, operand(y)?)),
+            ["tgl", x] => Ok(Instr::Tgl(operand(x)?)),
+            ["out", x] => Ok(Instr::Out(operand(x)?)),
+            _ => Err(ParseError::new(1, format!("unknown instruction {:?}", s))),
+        }
+    }
+}
+
+impl Display for Instr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Instr::Cpy(x, y) => write!(f, "cpy {} {}", x, y),
+            Instr::Inc(x) => write!(f, "inc {}", x),
+            Instr::Dec(x) => write!(f, "dec {}", x),
+            Instr::Jnz(x, y) => write!(f, "jnz {} {}", x, y),
+            Instr::Tgl(x) => write!(f, "tgl {}", x),
+            Instr::Out(x) => write!(f, "out {}", x),
+        }
+    }
+}
+
+pub fn parse_program(input: &str) -> Result<Vec<Instr>, ParseError> {
+    parse::lines_with(input, |line| line.trim().parse())
+}
+
+/// Why `Vm::run` returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// The program counter left the program.
+    Halted,
+    /// An `out` instruction produced a value; `run` can be called again to
+    /// continue.
+    Output(i64),
+    /// The instruction limit was reached.
+    LimitReached,
+}
+
+#[derive(Clone, Debug)]
+pub struct Vm {
+    program: Vec<Instr>,
+    registers: [i64; REGISTERS],
+    pc: usize,
+    executed: u64,
+    limit: Option<u64>,
+    optimize: bool,
+}
+
+impl Vm {
+    pub fn new(program: Vec<Instr>) -> Self {
+        Vm {
+            program,
+            registers: [0; REGISTERS],
+            pc: 0,
+            executed: 0,
+            limit: None,
+            optimize: true,
+        }
+    }
+
+    /// Sets a register, named `'a'` to `'d'`, before running.
+    pub fn with_register(mut self, name: char, value: i64) -> Self {
+        self.registers[register_index(name)] = value;
+        self
+    }
+
+    /// Stops with `Exit::LimitReached` once this many instructions have run.
+    /// The limit is checked between instructions, so an optimized loop can
+    /// overshoot it.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_optimizer(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+
+    pub fn register(&self, name: char) -> i64 {
+        self.registers[register_index(name)]
+    }
+
+    /// Instructions run so far, counting each pass through an optimized
+    /// loop as the instructions it replaced.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn executed(&self) -> u64 {
+        self.executed
+    }
+
+    fn value(&self, x: Operand) -> i64 {
+        match x {
+            Operand::Reg(r) => self.registers[r],
+            Operand::Imm(n) => n,
+        }
+    }
+
+    fn instr(&self, offset: usize) -> Option<Instr> {
+        self.program.get(self.pc + offset).copied()
+    }
+
+    /// Runs until the program halts, outputs a value or hits the limit.
+    pub fn run(&mut self) -> Exit {
+        loop {
+            if self.limit.is_some_and(|limit| self.executed >= limit) {
+                return Exit::LimitReached;
+            }
+            let instr = match self.instr(0) {
+                Some(instr) => instr,
+                None => return Exit::Halted,
+            };
+            if self.optimize && (self.multiply() || self.add()) {
+                continue;
+            }
+            self.executed += 1;
+            let mut next = self.pc as i64 + 1;
+            match instr {
+                Instr::Cpy(x, Operand::Reg(r)) => self.registers[r] = self.value(x),
+                Instr::Inc(Operand::Reg(r)) => self.registers[r] += 1,
+                Instr::Dec(Operand::Reg(r)) => self.registers[r] -= 1,
+                Instr::Jnz(x, y) if self.value(x) != 0 => next = self.pc as i64 + self.value(y),
+                Instr::Tgl(x) => {
+                    let target = self.pc as i64 + self.value(x);
+                    if let Some(instr) = usize::try_from(target)
+                        .ok()
+                        .and_then(|t| self.program.get_mut(t))
+                    {
+                        *instr = instr.toggled();
+                    }
+                }
+                Instr::Out(x) => {
+                    self.pc += 1;
+                    return Exit::Output(self.value(x));
+                }
+                _ => {}
+            }
+            match usize::try_from(next) {
+                Ok(pc) => self.pc = pc,
+                Err(_) => return Exit::Halted,
+            }
+        }
+    }
+
+    /// Runs to completion, discarding output.
+    pub fn run_to_halt(&mut self) -> Exit {
+        loop {
+            match self.run() {
+                Exit::Output(_) => {}
+                exit => return exit,
+            }
+        }
+    }
+
+    /// `inc a; dec b; jnz b -2` (in either order) adds `b` to `a`.
+    fn add(&mut self) -> bool {
+        let (a, b) = match (self.instr(0), self.instr(1), self.instr(2)) {
+            (
+                Some(Instr::Inc(Operand::Reg(a))),
+                Some(Instr::Dec(Operand::Reg(b))),
+                Some(Instr::Jnz(Operand::Reg(j), Operand::Imm(-2))),
+            )
+            | (
+                Some(Instr::Dec(Operand::Reg(b))),
+                Some(Instr::Inc(Operand::Reg(a))),
+                Some(Instr::Jnz(Operand::Reg(j), Operand::Imm(-2))),
+            ) if j == b && a != b => (a, b),
+            _ => return false,
+        };
+        let n = self.registers[b];
+        if n <= 0 {
+            return false;
+        }
+        self.registers[a] += n;
+        self.registers[b] = 0;
+        self.executed += 3 * n as u64;
+        self.pc += 3;
+        true
+    }
+
+    /// `cpy b c; inc a; dec c; jnz c -2; dec d; jnz d -5` adds `b * d` to
+    /// `a`.
+    fn multiply(&mut self) -> bool {
+        use Instr::{Cpy, Dec, Inc, Jnz};
+        use Operand::{Imm, Reg};
+
+        let window: [Option<Instr>; 6] = std::array::from_fn(|i| self.instr(i));
+        let (b, a, c, d) = match window {
+            [Some(Cpy(b, Reg(c))), Some(Inc(Reg(a))), Some(Dec(Reg(c2))), Some(Jnz(Reg(c3), Imm(-2))), Some(Dec(Reg(d))), Some(Jnz(Reg(d2), Imm(-5)))]
+                if c == c2 && c == c3 && d == d2 && a != c && a != d && c != d =>
+            {
+                (b, a, c, d)
+            }
+            _ => return false,
+        };
+        if [Reg(a), Reg(c), Reg(d)].contains(&b) {
+            return false;
+        }
+        let (m, n) = (self.value(b), self.registers[d]);
+        if m <= 0 || n <= 0 {
+            return false;
+        }
+        self.registers[a] += m * n;
+        self.registers[c] = 0;
+        self.registers[d] = 0;
+        self.executed += (3 * m as u64 + 3) * n as u64;
+        self.pc += 6;
+        true
+    }
+}
+
+fn register_index(name: char) -> usize {
+    match name {
+        'a'..='d' => name as usize - 'a' as usize,
+        _ => panic!("no register named {:?}", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_program, Exit, Instr, Operand, Vm};
+
+    const TOGGLE: &str = "cpy 2 a
+tgl a
+tgl a
+tgl a
+cpy 1 a
+dec a
+dec a";
+
+    const MULTIPLY: &str = "cpy 7 b
+cpy 6 d
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5";
+
+    #[test]
+    fn parsing() {
+        let program = parse_program("cpy 41 a\njnz c -2").unwrap();
+        assert_eq!(
+            program,
+            vec![
+                Instr::Cpy(Operand::Imm(41), Operand::Reg(0)),
+                Instr::Jnz(Operand::Reg(2), Operand::Imm(-2)),
+            ]
+        );
+        assert_eq!(program[1].to_string(), "jnz c -2");
+        assert_eq!(
+            parse_program("inc a\ncpy x a").unwrap_err().to_string(),
+            "line 2, column 5: invalid value \"x\": invalid digit found in string"
+        );
+        assert!(parse_program("mul a b").is_err());
+    }
+
+    #[test]
+    fn toggling() {
+        let mut vm = Vm::new(parse_program(TOGGLE).unwrap());
+        assert_eq!(vm.run(), Exit::Halted);
+        assert_eq!(vm.register('a'), 3);
+    }
+
+    #[test]
+    fn optimizer() {
+        let program = parse_program(MULTIPLY).unwrap();
+        let mut fast = Vm::new(program.clone());
+        let mut slow = Vm::new(program).with_optimizer(false);
+        assert_eq!(fast.run(), Exit::Halted);
+        assert_eq!(slow.run(), Exit::Halted);
+        assert_eq!(fast.register('a'), 42);
+        assert_eq!(slow.register('a'), 42);
+        assert_eq!(fast.executed(), slow.executed());
+    }
+
+    #[test]
+    fn output_and_limit() {
+        let program = parse_program("out a\ninc a\njnz 1 -2").unwrap();
+        let mut vm = Vm::new(program).with_register('a', 5);
+        let outputs: Vec<Exit> = (0..3).map(|_| vm.run()).collect();
+        assert_eq!(
+            outputs,
+            vec![Exit::Output(5), Exit::Output(6), Exit::Output(7)]
+        );
+
+        let mut vm = Vm::new(parse_program("jnz 1 0").unwrap()).with_limit(100);
+        assert_eq!(vm.run_to_halt(), Exit::LimitReached);
+        assert_eq!(vm.executed(), 100);
+    }
+}
@@ -0,0 +1,217 @@
+//! Classical ciphers over the Latin alphabet. Letters keep their case and
+//! anything that isn't an ASCII letter passes through unchanged.
+
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CipherError {
+    #[error("key must be a non-empty run of ASCII letters")]
+    InvalidKey,
+    #[error("substitution alphabet must contain each letter exactly once")]
+    InvalidAlphabet,
+}
+
+/// Relative frequency of each letter in English text, in percent.
+const ENGLISH: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+fn base(c: char) -> Option<u8> {
+    if c.is_ascii_lowercase() {
+        Some(b'a')
+    } else if c.is_ascii_uppercase() {
+        Some(b'A')
+    } else {
+        None
+    }
+}
+
+/// Shifts a letter `k` places forward, wrapping around the alphabet.
+pub fn shift_char(c: char, k: u32) -> char {
+    match base(c) {
+        Some(base) => {
+            let offset = (c as u8 - base) as u32;
+            (base + ((offset + k % 26) % 26) as u8) as char
+        }
+        None => c,
+    }
+}
+
+pub fn caesar(s: &str, k: u32) -> String {
+    s.chars().map(|c| shift_char(c, k)).collect()
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn rot13(s: &str) -> String {
+    caesar(s, 13)
+}
+
+fn key_shifts(key: &str) -> Result<Vec<u32>, CipherError> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(CipherError::InvalidKey);
+    }
+    Ok(key
+        .bytes()
+        .map(|b| (b.to_ascii_lowercase() - b'a') as u32)
+        .collect())
+}
+
+/// Applies the key's shifts to successive letters; non-letters don't
+/// consume key characters.
+fn vigenere(s: &str, key: &str, decrypt: bool) -> Result<String, CipherError> {
+    let shifts = key_shifts(key)?;
+    let mut keys = shifts.iter().cycle();
+    Ok(s.chars()
+        .map(|c| {
+            if base(c).is_none() {
+                return c;
+            }
+            let k = *keys.next().unwrap();
+            shift_char(c, if decrypt { 26 - k } else { k })
+        })
+        .collect())
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn vigenere_encrypt(s: &str, key: &str) -> Result<String, CipherError> {
+    vigenere(s, key, false)
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn vigenere_decrypt(s: &str, key: &str) -> Result<String, CipherError> {
+    vigenere(s, key, true)
+}
+
+/// Monoalphabetic substitution, given as the cipher letters for `a..=z`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Substitution {
+    forward: [u8; 26],
+    backward: [u8; 26],
+}
+
+impl Substitution {
+    fn map(table: &[u8; 26], c: char) -> char {
+        match base(c) {
+            Some(base) => (base + table[(c as u8 - base) as usize]) as char,
+            None => c,
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn encrypt(&self, s: &str) -> String {
+        s.chars().map(|c| Self::map(&self.forward, c)).collect()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn decrypt(&self, s: &str) -> String {
+        s.chars().map(|c| Self::map(&self.backward, c)).collect()
+    }
+}
+
+impl FromStr for Substitution {
+    type Err = CipherError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 26 || !bytes.iter().all(u8::is_ascii_alphabetic) {
+            return Err(CipherError::InvalidAlphabet);
+        }
+        let mut forward = [0; 26];
+        let mut backward = [u8::MAX; 26];
+        for (i, b) in bytes.iter().enumerate() {
+            let to = b.to_ascii_lowercase() - b'a';
+            if backward[to as usize] != u8::MAX {
+                return Err(CipherError::InvalidAlphabet);
+            }
+            forward[i] = to;
+            backward[to as usize] = i as u8;
+        }
+        Ok(Substitution { forward, backward })
+    }
+}
+
+/// Chi-squared distance between the letter distribution of `s` and English;
+/// lower is more English-like. Text without letters scores infinity.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn english_score(s: &str) -> f64 {
+    let mut counts = [0usize; 26];
+    for c in s.chars() {
+        if let Some(base) = base(c) {
+            counts[(c as u8 - base) as usize] += 1;
+        }
+    }
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return f64::INFINITY;
+    }
+    counts
+        .iter()
+        .zip(ENGLISH.iter())
+        .map(|(&n, &pct)| {
+            let expected = total as f64 * pct / 100.0;
+            (n as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Tries every Caesar shift and returns `(shift, plaintext)` pairs ordered
+/// from most to least English-like, where `shift` decrypts the text.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn brute_force_caesar(s: &str) -> Vec<(u32, String)> {
+    let mut candidates: Vec<(f64, u32, String)> = (0..26)
+        .map(|k| {
+            let plain = caesar(s, k);
+            (english_score(&plain), k, plain)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    candidates.into_iter().map(|(_, k, s)| (k, s)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        brute_force_caesar, caesar, rot13, vigenere_decrypt, vigenere_encrypt, CipherError,
+        Substitution,
+    };
+
+    #[test]
+    fn caesar_shifts() {
+        assert_eq!(caesar("qzmt-zixmtkozy-ivhz", 343), "very-encrypted-name");
+        assert_eq!(caesar("Hello, World!", 3), "Khoor, Zruog!");
+        assert_eq!(rot13(&rot13("Round Trip")), "Round Trip");
+    }
+
+    #[test]
+    fn vigenere_round_trip() {
+        let secret = vigenere_encrypt("Attack at dawn", "LEMON").unwrap();
+        assert_eq!(secret, "Lxfopv ef rnhr");
+        assert_eq!(
+            vigenere_decrypt(&secret, "lemon").unwrap(),
+            "Attack at dawn"
+        );
+        assert_eq!(vigenere_encrypt("x", "k3y"), Err(CipherError::InvalidKey));
+    }
+
+    #[test]
+    fn substitution() {
+        let sub: Substitution = "zebrascdfghijklmnopqtuvwxy".parse().unwrap();
+        assert_eq!(sub.encrypt("Flee at once"), "Siaa zq lkba");
+        assert_eq!(sub.decrypt("Siaa zq lkba"), "Flee at once");
+        assert_eq!(
+            "aabcdefghijklmnopqrstuvwxy".parse::<Substitution>(),
+            Err(CipherError::InvalidAlphabet)
+        );
+    }
+
+    #[test]
+    fn brute_force() {
+        let secret = caesar("the quick brown fox jumps over the lazy dog", 7);
+        let (shift, plain) = &brute_force_caesar(&secret)[0];
+        assert_eq!(*shift, 19);
+        assert_eq!(plain, "the quick brown fox jumps over the lazy dog");
+    }
+}
@@ -1,4 +1,4 @@
-use failure::Error;
+use crate::error::{AocError, ResultExt};
 use native_tls::TlsConnector;
 use std::collections::HashMap;
 use std::env::{self, VarError};
@@ -23,15 +23,19 @@ impl Client {
         }
     }
 
-    pub fn from_env() -> Result<Client, Error> {
-        let session = get_session_token()?;
+    pub fn from_env() -> Result<Client, AocError> {
+        let session = get_session_token()
+            .map_err(AocError::other)
+            .context(format!("reading {}", AOC_SESSION_ENV))?;
         Ok(Client::new(session))
     }
 
-    pub fn get_input(&self, year: i32, day: i32) -> Result<String, Error> {
-        let connector = TlsConnector::new()?;
+    pub fn get_input(&self, year: i32, day: i32) -> Result<String, AocError> {
+        let connector = TlsConnector::new().map_err(AocError::other)?;
         let stream = TcpStream::connect("adventofcode.com:443")?;
-        let mut stream = connector.connect("adventofcode.com", stream)?;
+        let mut stream = connector
+            .connect("adventofcode.com", stream)
+            .map_err(AocError::other)?;
 
         let nl = "\r\n";
         write!(stream, "GET /{}/day/{}/input HTTP/1.1{}", year, day, nl)?;
@@ -62,7 +66,7 @@ impl Client {
                 Some(s) => s,
                 None => return Err(HttpError::InvalidStatusLine.into()),
             };
-            code.parse()?
+            code.parse().map_err(|_| HttpError::InvalidStatusLine)?
         };
 
         if status != 200 {
@@ -93,13 +97,14 @@ impl Client {
                 .get("Content-Length")
                 .map(|s| &**s)
                 .unwrap_or("0")
-                .parse()?
+                .parse()
+                .map_err(|_| HttpError::InvalidHeader)?
         };
 
         let input = {
             let mut input_buf = vec![0u8; content_length];
             reader.read_exact(&mut input_buf)?;
-            String::from_utf8(input_buf)?
+            String::from_utf8(input_buf).map_err(AocError::other)?
         };
 
         Ok(input)
@@ -0,0 +1,249 @@
+//! Frequency counting with ranked queries.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("expected at least {expected} distinct items but found {found}")]
+pub struct CounterError {
+    pub expected: usize,
+    pub found: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Counter<T> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T> Default for Counter<T> {
+    fn default() -> Self {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Counter<T>
+where
+    T: Eq + Hash + Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, item: T) {
+        self.add_n(item, 1);
+    }
+
+    pub fn add_n(&mut self, item: T, n: usize) {
+        *self.counts.entry(item).or_insert(0) += n;
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn get(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct items.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Sum of all counts.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// All items sorted by count, with ties settled by `tie`.
+    fn ranked_by<F>(&self, descending: bool, mut tie: F) -> Vec<(T, usize)>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut items: Vec<(T, usize)> = self
+            .counts
+            .iter()
+            .map(|(item, &n)| (item.clone(), n))
+            .collect();
+        items.sort_by(|a, b| {
+            let by_count = if descending {
+                b.1.cmp(&a.1)
+            } else {
+                a.1.cmp(&b.1)
+            };
+            by_count.then_with(|| tie(&a.0, &b.0))
+        });
+        items
+    }
+
+    /// The `n` most common items, with equal counts ordered by `tie`.
+    pub fn most_common_by<F>(&self, n: usize, tie: F) -> Vec<(T, usize)>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut items = self.ranked_by(true, tie);
+        items.truncate(n);
+        items
+    }
+
+    /// The `n` least common items, with equal counts ordered by `tie`.
+    pub fn least_common_by<F>(&self, n: usize, tie: F) -> Vec<(T, usize)>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut items = self.ranked_by(false, tie);
+        items.truncate(n);
+        items
+    }
+}
+
+impl<T> Counter<T>
+where
+    T: Eq + Hash + Clone + Ord,
+{
+    /// The `n` most common items, with equal counts in ascending order.
+    pub fn most_common(&self, n: usize) -> Vec<(T, usize)> {
+        self.most_common_by(n, T::cmp)
+    }
+
+    /// The `n` least common items, with equal counts in ascending order.
+    pub fn least_common(&self, n: usize) -> Vec<(T, usize)> {
+        self.least_common_by(n, T::cmp)
+    }
+
+    pub fn most_common_one(&self) -> Option<T> {
+        self.most_common(1).pop().map(|(item, _)| item)
+    }
+
+    pub fn least_common_one(&self) -> Option<T> {
+        self.least_common(1).pop().map(|(item, _)| item)
+    }
+
+    /// The `N` most common items as an array, failing when there are fewer
+    /// than `N` distinct items.
+    pub fn most_common_array<const N: usize>(&self) -> Result<[T; N], CounterError> {
+        let found = self.len();
+        let items: Vec<T> = self
+            .most_common(N)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect();
+        items
+            .try_into()
+            .map_err(|_| CounterError { expected: N, found })
+    }
+}
+
+impl<T> Extend<T> for Counter<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Counter<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+/// Independent counters for each column of a sequence of rows. Rows may have
+/// different lengths; columns are added as wider rows appear.
+#[derive(Clone, Debug)]
+pub struct ColumnCounter<T> {
+    columns: Vec<Counter<T>>,
+}
+
+impl<T> Default for ColumnCounter<T> {
+    fn default() -> Self {
+        ColumnCounter {
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl<T> ColumnCounter<T>
+where
+    T: Eq + Hash + Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_row<I>(&mut self, row: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for (i, item) in row.into_iter().enumerate() {
+            if i == self.columns.len() {
+                self.columns.push(Counter::new());
+            }
+            self.columns[i].add(item);
+        }
+    }
+
+    pub fn columns(&self) -> &[Counter<T>] {
+        &self.columns
+    }
+}
+
+impl ColumnCounter<char> {
+    /// Counts the characters in each column of the lines of `s`.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn from_lines(s: &str) -> Self {
+        let mut counter = ColumnCounter::new();
+        for line in s.lines() {
+            counter.add_row(line.trim_end().chars());
+        }
+        counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColumnCounter, Counter, CounterError};
+
+    #[test]
+    fn ranking() {
+        let counter: Counter<char> = "aaaaabbbzyx".chars().collect();
+        assert_eq!(counter.get(&'a'), 5);
+        assert_eq!(counter.total(), 11);
+        assert_eq!(counter.most_common(3), vec![('a', 5), ('b', 3), ('x', 1)]);
+        assert_eq!(counter.least_common(2), vec![('x', 1), ('y', 1)]);
+        assert_eq!(
+            counter.most_common_by(3, |a, b| b.cmp(a)),
+            vec![('a', 5), ('b', 3), ('z', 1)]
+        );
+        assert_eq!(counter.most_common_array(), Ok(['a', 'b', 'x', 'y', 'z']));
+        assert_eq!(
+            counter.most_common_array::<6>(),
+            Err(CounterError {
+                expected: 6,
+                found: 5
+            })
+        );
+    }
+
+    #[test]
+    fn columns() {
+        let counter = ColumnCounter::from_lines("abc\nabd\nxb\nxyzw");
+        let columns = counter.columns();
+        assert_eq!(columns.len(), 4);
+        assert_eq!(columns[1].most_common_one(), Some('b'));
+        assert_eq!(columns[2].least_common_one(), Some('c'));
+        assert_eq!(columns[3].total(), 1);
+    }
+}
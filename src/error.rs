@@ -0,0 +1,125 @@
+//! Crate-wide error type so the runner can report failures from any day in
+//! the same way.
+
+use crate::client::HttpError;
+use crate::grid::GridError;
+use crate::ocr::OcrError;
+use crate::parse::ParseError;
+use std::error::Error as StdError;
+use std::fmt::Display;
+use std::io;
+use thiserror::Error;
+
+pub type BoxError = Box<dyn StdError + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum AocError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Grid(#[from] GridError),
+    #[error(transparent)]
+    Ocr(#[from] OcrError),
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("line {line}")]
+    AtLine {
+        line: usize,
+        #[source]
+        source: Box<AocError>,
+    },
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<AocError>,
+    },
+    #[error(transparent)]
+    Other(BoxError),
+}
+
+impl AocError {
+    pub fn invalid(message: impl Display) -> Self {
+        AocError::Invalid(message.to_string())
+    }
+
+    pub fn other<E>(err: E) -> Self
+    where
+        E: StdError + Send + Sync + 'static,
+    {
+        AocError::Other(Box::new(err))
+    }
+
+    pub fn at_line(self, line: usize) -> Self {
+        AocError::AtLine {
+            line,
+            source: Box::new(self),
+        }
+    }
+
+    pub fn context(self, context: impl Display) -> Self {
+        AocError::Context {
+            context: context.to_string(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Renders the error followed by its chain of causes, one per line.
+    pub fn report(&self) -> String {
+        let mut out = self.to_string();
+        let mut source = self.source();
+        while let Some(err) = source {
+            out.push_str(&format!("\n  caused by: {}", err));
+            source = err.source();
+        }
+        out
+    }
+}
+
+pub trait ResultExt<T> {
+    fn context(self, context: impl Display) -> Result<T, AocError>;
+    fn at_line(self, line: usize) -> Result<T, AocError>;
+}
+
+impl<T, E> ResultExt<T> for Result<T, E>
+where
+    E: Into<AocError>,
+{
+    fn context(self, context: impl Display) -> Result<T, AocError> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn at_line(self, line: usize) -> Result<T, AocError> {
+        self.map_err(|e| e.into().at_line(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AocError, ResultExt};
+    use crate::parse;
+
+    #[test]
+    fn chained_report() {
+        let err = parse::lines_of::<u32>("1\nx")
+            .context("reading numbers")
+            .unwrap_err();
+        assert_eq!(
+            err.report(),
+            "reading numbers\n  caused by: line 2, column 1: invalid value \"x\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn line_context() {
+        let err: Result<(), _> = Err(AocError::invalid("unexpected 'X'"));
+        assert_eq!(
+            err.at_line(4).unwrap_err().report(),
+            "line 4\n  caused by: unexpected 'X'"
+        );
+    }
+}
@@ -0,0 +1,393 @@
+//! Shared 2D geometry for grid and turtle style puzzles.
+//!
+//! Coordinates use screen orientation: `x` grows to the east and `y` grows
+//! to the south, so `North` is `(0, -1)`. This matches row/column indexing
+//! of character maps.
+
+use num::{Signed, Zero};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T: Zero> Point<T> {
+    pub fn origin() -> Self {
+        Point::new(T::zero(), T::zero())
+    }
+}
+
+impl<T: Signed + Copy + PartialOrd> Point<T> {
+    pub fn manhattan(self, other: Self) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        let dx = (self.x - other.x).abs();
+        let dy = (self.y - other.y).abs();
+        if dx > dy {
+            dx
+        } else {
+            dy
+        }
+    }
+
+    pub fn step(self, dir: Direction) -> Self {
+        self + dir.delta()
+    }
+
+    pub fn neighbors4(self) -> impl Iterator<Item = Self> {
+        Direction::CARDINAL.iter().map(move |&d| self.step(d))
+    }
+
+    pub fn neighbors8(self) -> impl Iterator<Item = Self> {
+        Direction::ALL.iter().map(move |&d| self.step(d))
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        Point::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point::new(x, y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl FromStr for Turn {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "L" => Ok(Turn::Left),
+            "R" => Ok(Turn::Right),
+            _ => Err("expected 'L' or 'R'"),
+        }
+    }
+}
+
+/// Compass directions, ordered clockwise starting from north.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Direction {
+    #[default]
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Rotates clockwise by the given number of 45 degree increments;
+    /// negative values rotate counter-clockwise.
+    pub fn rotate(self, eighths: i32) -> Self {
+        Direction::ALL[(self.index() as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Left => self.rotate(-2),
+            Turn::Right => self.rotate(2),
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn delta<T: Signed>(self) -> Point<T> {
+        let (dx, dy) = match self {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, -1),
+        };
+        let unit = |d: i8| match d {
+            -1 => -T::one(),
+            0 => T::zero(),
+            _ => T::one(),
+        };
+        Point::new(unit(dx), unit(dy))
+    }
+}
+
+/// Smallest axis-aligned rectangle containing a set of points, with
+/// inclusive bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Bounds<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Signed + Copy + Ord> Bounds<T> {
+    pub fn new(point: Point<T>) -> Self {
+        Bounds {
+            min: point,
+            max: point,
+        }
+    }
+
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Point<T>>,
+    {
+        let mut points = points.into_iter();
+        let mut bounds = Bounds::new(points.next()?);
+        for point in points {
+            bounds.include(point);
+        }
+        Some(bounds)
+    }
+
+    pub fn include(&mut self, point: Point<T>) {
+        self.min = Point::new(self.min.x.min(point.x), self.min.y.min(point.y));
+        self.max = Point::new(self.max.x.max(point.x), self.max.y.max(point.y));
+    }
+
+    pub fn contains(&self, point: Point<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::one()
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::one()
+    }
+}
+
+/// Axis-aligned line segment with inclusive endpoints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Segment<T> {
+    pub start: Point<T>,
+    pub end: Point<T>,
+}
+
+impl<T: Signed + Copy + Ord> Segment<T> {
+    pub fn new(start: Point<T>, end: Point<T>) -> Self {
+        debug_assert!(
+            start.x == end.x || start.y == end.y,
+            "segment not axis-aligned"
+        );
+        Segment { start, end }
+    }
+
+    pub fn len(&self) -> T {
+        self.start.chebyshev(self.end)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn contains(&self, p: Point<T>) -> bool {
+        let mut bounds = Bounds::new(self.start);
+        bounds.include(self.end);
+        bounds.contains(p)
+    }
+
+    /// Returns the first point after `start`, walking towards `end`, that
+    /// also lies on `other`. Handles both crossing and overlapping segments.
+    pub fn first_hit(&self, other: &Segment<T>) -> Option<Point<T>> {
+        let delta = Point::new(
+            (self.end.x - self.start.x).signum(),
+            (self.end.y - self.start.y).signum(),
+        );
+        // Solve for the range of steps `t` that keep each coordinate within
+        // the other segment's extent on that axis.
+        let axis = |s: T, d: T, a: T, b: T| -> Option<(T, T)> {
+            let (lo, hi) = (a.min(b), a.max(b));
+            if d.is_zero() {
+                if lo <= s && s <= hi {
+                    Some((T::one(), self.len()))
+                } else {
+                    None
+                }
+            } else {
+                let (t0, t1) = ((lo - s) * d, (hi - s) * d);
+                Some((t0.min(t1), t0.max(t1)))
+            }
+        };
+        let (xlo, xhi) = axis(self.start.x, delta.x, other.start.x, other.end.x)?;
+        let (ylo, yhi) = axis(self.start.y, delta.y, other.start.y, other.end.y)?;
+        let lo = T::one().max(xlo).max(ylo);
+        let hi = self.len().min(xhi).min(yhi);
+        if lo <= hi {
+            Some(self.start + delta * lo)
+        } else {
+            None
+        }
+    }
+}
+
+/// A walker with a position and a heading.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Turtle<T> {
+    pub position: Point<T>,
+    pub facing: Direction,
+}
+
+impl<T: Signed + Copy + PartialOrd> Turtle<T> {
+    pub fn turn(&mut self, turn: Turn) -> &mut Self {
+        self.facing = self.facing.turn(turn);
+        self
+    }
+
+    pub fn forward(&mut self, n: T) -> &mut Self {
+        self.position = self.position + self.facing.delta() * n;
+        self
+    }
+
+    pub fn step(&mut self) -> &mut Self {
+        self.position = self.position.step(self.facing);
+        self
+    }
+
+    pub fn distance(&self) -> T {
+        self.position.manhattan(Point::origin())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bounds, Direction, Point, Segment, Turn, Turtle};
+
+    #[test]
+    fn rotation() {
+        assert_eq!(Direction::North.turn(Turn::Right), Direction::East);
+        assert_eq!(Direction::North.turn(Turn::Left), Direction::West);
+        assert_eq!(Direction::West.rotate(3), Direction::NorthEast);
+        assert_eq!(Direction::SouthEast.reverse(), Direction::NorthWest);
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point::new(1, -2);
+        let b = Point::new(-3, 5);
+        assert_eq!(a.manhattan(b), 11);
+        assert_eq!(a.chebyshev(b), 7);
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds =
+            Bounds::from_points(vec![Point::new(2, 3), Point::new(-1, 0), Point::new(4, -2)])
+                .unwrap();
+        assert_eq!(bounds.min, Point::new(-1, -2));
+        assert_eq!(bounds.max, Point::new(4, 3));
+        assert_eq!((bounds.width(), bounds.height()), (6, 6));
+        assert!(bounds.contains(Point::new(0, 0)));
+        assert!(!bounds.contains(Point::new(5, 0)));
+    }
+
+    #[test]
+    fn segments() {
+        let path = Segment::new(Point::new(0, 0), Point::new(10, 0));
+        let crossing = Segment::new(Point::new(4, -3), Point::new(4, 3));
+        let overlap = Segment::new(Point::new(12, 0), Point::new(6, 0));
+        let touching = Segment::new(Point::new(0, 5), Point::new(0, 0));
+        assert_eq!(path.len(), 10);
+        assert_eq!(path.first_hit(&crossing), Some(Point::new(4, 0)));
+        assert_eq!(path.first_hit(&overlap), Some(Point::new(6, 0)));
+        assert_eq!(path.first_hit(&touching), None);
+        assert_eq!(crossing.first_hit(&path), Some(Point::new(4, 0)));
+        assert!(overlap.contains(Point::new(10, 0)));
+    }
+
+    #[test]
+    fn turtle() {
+        let mut turtle = Turtle::<i32>::default();
+        turtle
+            .turn(Turn::Right)
+            .forward(3)
+            .turn(Turn::Left)
+            .forward(2);
+        assert_eq!(turtle.position, Point::new(3, -2));
+        assert_eq!(turtle.distance(), 5);
+    }
+}
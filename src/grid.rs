@@ -0,0 +1,342 @@
+//! Dense, row-major 2D grid addressed by `Point<i64>`, with `y` growing
+//! downwards so that parsed maps keep their on-screen layout.
+
+use crate::geom::Point;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use thiserror::Error;
+
+pub type Pos = Point<i64>;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum GridError {
+    #[error("row {row} has width {found}, expected {expected}")]
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("invalid cell {ch:?} at row {row}, column {col}")]
+    InvalidCell { row: usize, col: usize, ch: char },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(Pos) -> T,
+    {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(Pos::new(x as i64, y as i64)));
+            }
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses a map with one cell per character. Every line must have the
+    /// same width; a trailing newline is ignored.
+    pub fn parse_with<F>(s: &str, mut f: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (row, line) in s.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            let start = cells.len();
+            for (col, ch) in line.chars().enumerate() {
+                cells.push(f(ch).ok_or(GridError::InvalidCell { row, col, ch })?);
+            }
+            let found = cells.len() - start;
+            let expected = *width.get_or_insert(found);
+            if found != expected {
+                return Err(GridError::Ragged {
+                    row,
+                    expected,
+                    found,
+                });
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn offset(&self, p: Pos) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, p: Pos) -> bool {
+        (0..self.width as i64).contains(&p.x) && (0..self.height as i64).contains(&p.y)
+    }
+
+    pub fn get(&self, p: Pos) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn get_mut(&mut self, p: Pos) -> Option<&mut T> {
+        self.offset(p).map(move |i| &mut self.cells[i])
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Pos> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        (0..height).flat_map(move |y| (0..width).map(move |x| Pos::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn position<F>(&self, mut pred: F) -> Option<Pos>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().find(|(_, v)| pred(v)).map(|(p, _)| p)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn neighbors4(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        p.neighbors4().filter(move |&n| self.contains(n))
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn neighbors8(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        p.neighbors8().filter(move |&n| self.contains(n))
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` rejects a zero size, which only happens for empty grids.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {} out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Returns every cell reachable from `start` through orthogonal moves
+    /// onto cells accepted by `passable`, including `start` itself.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn flood_fill<F>(&self, start: Pos, passable: F) -> HashSet<Pos>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut seen = HashSet::new();
+        if !self.get(start).is_some_and(&passable) {
+            return seen;
+        }
+        let mut queue = VecDeque::new();
+        seen.insert(start);
+        queue.push_back(start);
+        while let Some(p) = queue.pop_front() {
+            for n in self.neighbors4(p) {
+                if passable(&self[n]) && seen.insert(n) {
+                    queue.push_back(n);
+                }
+            }
+        }
+        seen
+    }
+
+    pub fn render<F>(&self, f: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&f));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn transpose(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |p| {
+            self[Pos::new(p.y, p.x)].clone()
+        })
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn rotate_cw(&self) -> Self {
+        let h = self.height as i64;
+        Grid::from_fn(self.height, self.width, |p| {
+            self[Pos::new(p.y, h - 1 - p.x)].clone()
+        })
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn rotate_ccw(&self) -> Self {
+        let w = self.width as i64;
+        Grid::from_fn(self.height, self.width, |p| {
+            self[Pos::new(w - 1 - p.y, p.x)].clone()
+        })
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Pos) -> &T {
+        match self.offset(p) {
+            Some(i) => &self.cells[i],
+            None => panic!("{:?} out of bounds", p),
+        }
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, p: Pos) -> &mut T {
+        match self.offset(p) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("{:?} out of bounds", p),
+        }
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(s, Some)
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, GridError, Pos};
+
+    const MAP: &str = "#..
+.##
+...
+##.";
+
+    #[test]
+    fn parse_and_index() {
+        let grid: Grid<char> = MAP.parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert_eq!(grid[Pos::new(1, 1)], '#');
+        assert_eq!(grid.get(Pos::new(3, 0)), None);
+        assert_eq!(grid.get(Pos::new(0, -1)), None);
+        assert_eq!(grid.row(3), &['#', '#', '.']);
+        assert_eq!(grid.column(2).collect::<String>(), ".#..");
+        let columns: Vec<String> = grid.columns().map(|c| c.collect()).collect();
+        assert_eq!(columns, vec!["#..#", ".#.#", ".#.."]);
+        assert_eq!(grid.to_string(), format!("{}\n", MAP));
+    }
+
+    #[test]
+    fn mutation() {
+        let mut grid: Grid<char> = MAP.parse().unwrap();
+        *grid.get_mut(Pos::new(0, 0)).unwrap() = '.';
+        assert_eq!(grid.row(0), &['.', '.', '.']);
+        assert!(grid.get_mut(Pos::new(0, 4)).is_none());
+    }
+
+    #[test]
+    fn ragged() {
+        assert_eq!(
+            "ab\nc".parse::<Grid<char>>(),
+            Err(GridError::Ragged {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid: Grid<char> = MAP.parse().unwrap();
+        assert_eq!(grid.neighbors4(Pos::new(0, 0)).count(), 2);
+        assert_eq!(grid.neighbors8(Pos::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors8(Pos::new(2, 3)).count(), 3);
+    }
+
+    #[test]
+    fn transform() {
+        let grid: Grid<char> = "abc\ndef".parse().unwrap();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad\n");
+    }
+
+    #[test]
+    fn flood_fill() {
+        let grid: Grid<char> = MAP.parse().unwrap();
+        assert_eq!(grid.flood_fill(Pos::new(1, 0), |&c| c == '.').len(), 2);
+        assert_eq!(grid.flood_fill(Pos::new(0, 1), |&c| c == '.').len(), 5);
+        assert!(grid.flood_fill(Pos::new(0, 0), |&c| c == '.').is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+//! Brute-force search for MD5 digests of `prefix + nonce`, where the nonce
+//! is written in decimal.
+//!
+//! Nonces are processed in batches split across all available cores, and
+//! matches are yielded in nonce order so results do not depend on thread
+//! scheduling. Digests can optionally be stretched by re-hashing their hex
+//! representation, and `HashWindow` memoizes digests for puzzles that look
+//! ahead of the current nonce.
+
+use md5::{Digest as _, Md5};
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::thread;
+
+pub type Digest = [u8; 16];
+
+/// Longest decimal representation of a `u64`.
+const MAX_DIGITS: usize = 20;
+
+/// Writes `n` in decimal to the end of `buf` and returns the used suffix.
+fn decimal(n: u64, buf: &mut [u8; MAX_DIGITS]) -> &[u8] {
+    let mut n = n;
+    let mut i = buf.len();
+    loop {
+        i -= 1;
+        buf[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return &buf[i..];
+        }
+    }
+}
+
+/// Hashes a single nonce on top of an already absorbed prefix.
+pub fn digest_with(prefix: &Md5, nonce: u64) -> Digest {
+    let mut buf = [0; MAX_DIGITS];
+    let mut hasher = prefix.clone();
+    hasher.update(decimal(nonce, &mut buf));
+    hasher.finalize().into()
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn digest(prefix: &str, nonce: u64) -> Digest {
+    digest_with(&Md5::new_with_prefix(prefix), nonce)
+}
+
+pub fn hex_char(n: u8) -> char {
+    b"0123456789abcdef"[n as usize] as char
+}
+
+pub fn to_hex(d: &Digest) -> [u8; 32] {
+    let mut out = [0; 32];
+    for (i, n) in nibbles(d).enumerate() {
+        out[i] = hex_char(n) as u8;
+    }
+    out
+}
+
+/// Re-hashes the lowercase hex form of a digest `rounds` times.
+pub fn stretch(mut d: Digest, rounds: usize) -> Digest {
+    for _ in 0..rounds {
+        d = Md5::digest(to_hex(&d)).into();
+    }
+    d
+}
+
+/// The `i`-th hex digit of a digest, counting from the most significant.
+pub fn nibble(d: &Digest, i: usize) -> u8 {
+    let b = d[i / 2];
+    if i.is_multiple_of(2) {
+        b >> 4
+    } else {
+        b & 0x0f
+    }
+}
+
+pub fn nibbles(d: &Digest) -> impl Iterator<Item = u8> + '_ {
+    d.iter().flat_map(|&b| [b >> 4, b & 0x0f])
+}
+
+pub fn leading_zeros(d: &Digest) -> usize {
+    nibbles(d).take_while(|&n| n == 0).count()
+}
+
+/// The first hex digit repeated at least `len` times in a row, such as the
+/// first triple for `len == 3`.
+pub fn first_run(d: &Digest, len: usize) -> Option<u8> {
+    runs(d).find(|&(_, n)| n >= len).map(|(x, _)| x)
+}
+
+/// Whether `x` appears at least `len` times in a row, such as a quintuple
+/// of `x` for `len == 5`.
+pub fn has_run(d: &Digest, x: u8, len: usize) -> bool {
+    runs(d).any(|(y, n)| y == x && n >= len)
+}
+
+/// Maximal runs of equal hex digits as `(digit, length)` pairs.
+fn runs(d: &Digest) -> impl Iterator<Item = (u8, usize)> + '_ {
+    let mut nibbles = nibbles(d).peekable();
+    std::iter::from_fn(move || {
+        let x = nibbles.next()?;
+        let mut len = 1;
+        while nibbles.next_if_eq(&x).is_some() {
+            len += 1;
+        }
+        Some((x, len))
+    })
+}
+
+pub struct HashSearch<P> {
+    prefix: Md5,
+    predicate: P,
+    next: u64,
+    chunk: u64,
+    threads: usize,
+    rounds: usize,
+    found: VecDeque<(u64, Digest)>,
+}
+
+impl<P> HashSearch<P>
+where
+    P: Fn(&Digest) -> bool + Sync,
+{
+    pub fn new(prefix: &str, predicate: P) -> Self {
+        HashSearch {
+            prefix: Md5::new_with_prefix(prefix),
+            predicate,
+            next: 0,
+            chunk: 1 << 14,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            rounds: 0,
+            found: VecDeque::new(),
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn start(mut self, nonce: u64) -> Self {
+        self.next = nonce;
+        self
+    }
+
+    /// Number of nonces each thread hashes per batch.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn chunk(mut self, chunk: u64) -> Self {
+        self.chunk = chunk.max(1);
+        self
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Stretches every digest by the given number of extra rounds before
+    /// testing it.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn stretch(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Nonces below this have all been hashed.
+    pub fn searched(&self) -> u64 {
+        self.next
+    }
+
+    /// Hashes the next batch of nonces and returns its matches, after any
+    /// already found but not yet yielded, in nonce order. Together with
+    /// `searched` this lets callers report progress between matches.
+    pub fn next_batch(&mut self) -> Vec<(u64, Digest)> {
+        self.batch();
+        self.found.drain(..).collect()
+    }
+
+    fn search(&self, from: u64, to: u64) -> Vec<(u64, Digest)> {
+        (from..to)
+            .filter_map(|nonce| {
+                let d = stretch(digest_with(&self.prefix, nonce), self.rounds);
+                if (self.predicate)(&d) {
+                    Some((nonce, d))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn batch(&mut self) {
+        let start = self.next;
+        let this = &*self;
+        let results = thread::scope(|s| {
+            let workers: Vec<_> = (0..this.threads as u64)
+                .map(|i| {
+                    let from = start + i * this.chunk;
+                    s.spawn(move || this.search(from, from + this.chunk))
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|w| w.join().expect("hash search worker panicked"))
+                .collect::<Vec<_>>()
+        });
+        self.next = start + this.threads as u64 * this.chunk;
+        self.found.extend(results.into_iter().flatten());
+    }
+}
+
+impl<P> Iterator for HashSearch<P>
+where
+    P: Fn(&Digest) -> bool + Sync,
+{
+    type Item = (u64, Digest);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.found.is_empty() {
+            self.batch();
+        }
+        self.found.pop_front()
+    }
+}
+
+/// Lazily computed digests for a sliding range of nonces, so that
+/// look-ahead checks hash every nonce only once.
+///
+/// Digests are kept in a ring buffer and computed ahead in batches spread
+/// across threads, which pays off when each digest is expensive, as with
+/// stretching.
+pub struct HashWindow<F> {
+    hash: F,
+    start: u64,
+    cache: VecDeque<Digest>,
+    chunk: u64,
+    threads: usize,
+}
+
+impl<F> HashWindow<F>
+where
+    F: Fn(u64) -> Digest + Sync,
+{
+    pub fn new(hash: F) -> Self {
+        HashWindow {
+            hash,
+            start: 0,
+            cache: VecDeque::new(),
+            chunk: 64,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Number of nonces each thread hashes when the window grows.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn chunk(mut self, chunk: u64) -> Self {
+        self.chunk = chunk.max(1);
+        self
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Appends the digests for the next `threads * chunk` nonces.
+    fn grow(&mut self) {
+        let from = self.start + self.cache.len() as u64;
+        let (hash, chunk) = (&self.hash, self.chunk);
+        let batches = thread::scope(|s| {
+            let workers: Vec<_> = (0..self.threads as u64)
+                .map(|i| {
+                    let from = from + i * chunk;
+                    s.spawn(move || (from..from + chunk).map(hash).collect::<Vec<_>>())
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|w| w.join().expect("hash window worker panicked"))
+                .collect::<Vec<_>>()
+        });
+        self.cache.extend(batches.into_iter().flatten());
+    }
+
+    /// Returns the digest for `nonce`, which must not have been evicted.
+    pub fn get(&mut self, nonce: u64) -> &Digest {
+        assert!(nonce >= self.start, "nonce {} already evicted", nonce);
+        let offset = (nonce - self.start) as usize;
+        while self.cache.len() <= offset {
+            self.grow();
+        }
+        &self.cache[offset]
+    }
+
+    /// Drops cached digests for nonces before `nonce`.
+    pub fn evict_before(&mut self, nonce: u64) {
+        let drop = (nonce.saturating_sub(self.start) as usize).min(self.cache.len());
+        self.cache.drain(..drop);
+        self.start = self.start.max(nonce);
+    }
+
+    pub fn any<P>(&mut self, nonces: Range<u64>, mut pred: P) -> bool
+    where
+        P: FnMut(&Digest) -> bool,
+    {
+        nonces.into_iter().any(|n| pred(self.get(n)))
+    }
+}
+
+/// A window over `prefix + nonce` digests stretched by `rounds`.
+pub fn md5_window(prefix: &str, rounds: usize) -> HashWindow<impl Fn(u64) -> Digest + Sync> {
+    let prefix = Md5::new_with_prefix(prefix);
+    HashWindow::new(move |nonce| stretch(digest_with(&prefix, nonce), rounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decimal, digest, first_run, has_run, leading_zeros, md5_window, nibble, stretch, to_hex,
+        Digest, HashSearch,
+    };
+
+    #[test]
+    fn decimal_suffix() {
+        let mut buf = [0; 20];
+        assert_eq!(decimal(0, &mut buf), b"0");
+        assert_eq!(decimal(3231929, &mut buf), b"3231929");
+        assert_eq!(decimal(u64::MAX, &mut buf), b"18446744073709551615");
+    }
+
+    #[test]
+    fn nibble_queries() {
+        let d = digest("abc", 3231929);
+        assert_eq!(&to_hex(&d)[..8], b"00000155");
+        assert_eq!(leading_zeros(&d), 5);
+        assert_eq!(nibble(&d, 5), 1);
+        assert_eq!(first_run(&d, 3), Some(0));
+        assert!(has_run(&d, 0, 5));
+        assert!(has_run(&d, 5, 2));
+        assert!(!has_run(&d, 5, 3));
+    }
+
+    #[test]
+    fn stretching() {
+        let d = digest("abc", 0);
+        assert_eq!(&to_hex(&d)[..8], b"577571be");
+        assert_eq!(&to_hex(&stretch(d, 2016))[..8], b"a107ff63");
+    }
+
+    #[test]
+    fn window() {
+        let mut window = md5_window("abc", 0);
+        assert_eq!(first_run(window.get(18), 3), Some(8));
+        assert!(!window.any(19..1019, |d| has_run(d, 8, 5)));
+        assert_eq!(first_run(window.get(39), 3), Some(0xe));
+        assert!(window.any(40..1040, |d| has_run(d, 0xe, 5)));
+        window.evict_before(39);
+        assert_eq!(*window.get(39), digest("abc", 39));
+        window.evict_before(5000);
+        assert_eq!(*window.get(5000), digest("abc", 5000));
+    }
+
+    #[test]
+    fn threaded_window() {
+        let mut window = md5_window("abc", 2).chunk(5).threads(3);
+        for n in [0, 14, 15, 31, 7] {
+            assert_eq!(*window.get(n), stretch(digest("abc", n), 2));
+        }
+    }
+
+    #[test]
+    fn ordered_matches() {
+        let expected: Vec<_> = (0..2000)
+            .map(|n| (n, digest("abc", n)))
+            .filter(|(_, d)| d[0] == 0)
+            .collect();
+        let found: Vec<_> = HashSearch::new("abc", |d| d[0] == 0)
+            .chunk(100)
+            .threads(3)
+            .take_while(|&(n, _)| n < 2000)
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn batches() {
+        let mut search = HashSearch::new("abc", |d| d[0] == 0).chunk(100).threads(2);
+        let first = search.next().unwrap();
+        assert_eq!(search.searched(), 200);
+        let mut found = vec![first];
+        while search.searched() < 2000 {
+            found.extend(search.next_batch());
+        }
+        assert_eq!(search.searched(), 2000);
+        let expected: Vec<_> = (0..2000)
+            .map(|n| (n, digest("abc", n)))
+            .filter(|(_, d)| d[0] == 0)
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn stretched_search() {
+        let first_nibble_zero = |d: &Digest| d[0] >> 4 == 0;
+        let expected = (0..)
+            .map(|n| (n, stretch(digest("abc", n), 2016)))
+            .find(|(_, d)| first_nibble_zero(d))
+            .unwrap();
+        let found = HashSearch::new("abc", first_nibble_zero)
+            .stretch(2016)
+            .chunk(8)
+            .threads(2)
+            .next()
+            .unwrap();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn start_offset() {
+        let (nonce, _) = HashSearch::new("abc", |d| d[..2] == [0, 0])
+            .start(3231929)
+            .next()
+            .unwrap();
+        assert_eq!(nonce, 3231929);
+    }
+}
@@ -0,0 +1,176 @@
+//! Keypads of arbitrary shape, described by ASCII layouts where every
+//! non-space character is a key and spaces are holes.
+
+use crate::geom::Direction;
+use crate::grid::{Grid, GridError, Pos};
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+pub struct Keypad {
+    layout: Grid<char>,
+}
+
+impl Keypad {
+    pub fn key(&self, pos: Pos) -> Option<char> {
+        self.layout.get(pos).copied().filter(|&c| c != ' ')
+    }
+
+    pub fn find(&self, key: char) -> Option<Pos> {
+        if key == ' ' {
+            return None;
+        }
+        self.layout.position(|&c| c == key)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn keys(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        self.layout
+            .iter()
+            .filter(|(_, &c)| c != ' ')
+            .map(|(p, &c)| (p, c))
+    }
+
+    /// Moves one key in `dir`, staying put if that would leave the keypad.
+    pub fn step_with(&self, pos: Pos, dir: Direction) -> Pos {
+        let next = pos.step(dir);
+        if self.key(next).is_some() {
+            next
+        } else {
+            pos
+        }
+    }
+
+    pub fn step_all_with<I>(&self, pos: Pos, dirs: I) -> Pos
+    where
+        I: IntoIterator<Item = Direction>,
+    {
+        dirs.into_iter()
+            .fold(pos, |pos, dir| self.step_with(pos, dir))
+    }
+
+    fn distances(&self, from: Pos) -> HashMap<Pos, usize> {
+        let mut dist = HashMap::new();
+        let mut queue = VecDeque::new();
+        dist.insert(from, 0);
+        queue.push_back(from);
+        while let Some(p) = queue.pop_front() {
+            let d = dist[&p];
+            for &dir in &Direction::CARDINAL {
+                let n = p.step(dir);
+                if self.key(n).is_some() && !dist.contains_key(&n) {
+                    dist.insert(n, d + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Returns every minimal sequence of moves from key `from` to key `to`,
+    /// in lexicographic order of direction. Returns no sequences when either
+    /// key is missing or `to` cannot be reached.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn shortest_paths(&self, from: char, to: char) -> Vec<Vec<Direction>> {
+        let (start, end) = match (self.find(from), self.find(to)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Vec::new(),
+        };
+        let dist = self.distances(start);
+        if !dist.contains_key(&end) {
+            return Vec::new();
+        }
+
+        // Walk backwards from the target along strictly decreasing distances.
+        let mut paths = Vec::new();
+        let mut stack = vec![(end, Vec::new())];
+        while let Some((pos, suffix)) = stack.pop() {
+            if pos == start {
+                let mut path: Vec<Direction> = suffix;
+                path.reverse();
+                paths.push(path);
+                continue;
+            }
+            for &dir in Direction::CARDINAL.iter().rev() {
+                let prev = pos.step(dir.reverse());
+                if dist.get(&prev).map(|d| d + 1) == Some(dist[&pos]) {
+                    let mut next = suffix.clone();
+                    next.push(dir);
+                    stack.push((prev, next));
+                }
+            }
+        }
+        paths.sort_by_key(|path| path.iter().map(|&d| d as u8).collect::<Vec<_>>());
+        paths
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn shortest_path(&self, from: char, to: char) -> Option<Vec<Direction>> {
+        self.shortest_paths(from, to).into_iter().next()
+    }
+}
+
+impl FromStr for Keypad {
+    type Err = GridError;
+
+    /// Parses a layout, padding rows shorter than the widest one with holes
+    /// so that trailing spaces may be left out.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<Vec<char>> = s
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().collect())
+            .collect();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let layout = Grid::from_fn(width, rows.len(), |p| {
+            rows[p.y as usize].get(p.x as usize).copied().unwrap_or(' ')
+        });
+        Ok(Keypad { layout })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keypad;
+    use crate::geom::Direction::{East, North, South, West};
+    use crate::grid::Pos;
+
+    const NUMERIC: &str = "789\n456\n123\n 0A";
+
+    #[test]
+    fn holes() {
+        let keypad: Keypad = NUMERIC.parse().unwrap();
+        assert_eq!(keypad.key(Pos::new(0, 3)), None);
+        assert_eq!(keypad.find('0'), Some(Pos::new(1, 3)));
+        assert_eq!(keypad.find(' '), None);
+        assert_eq!(keypad.keys().count(), 11);
+
+        let one = keypad.find('1').unwrap();
+        assert_eq!(keypad.step_with(one, South), one);
+        assert_eq!(keypad.step_all_with(one, vec![East, South]), Pos::new(1, 3));
+    }
+
+    #[test]
+    fn trimmed_rows() {
+        let keypad: Keypad = "  1\n 234\n56789".parse().unwrap();
+        assert_eq!(keypad.keys().count(), 9);
+        assert_eq!(keypad.key(Pos::new(3, 0)), None);
+        assert_eq!(keypad.find('4'), Some(Pos::new(3, 1)));
+        let one = keypad.find('1').unwrap();
+        assert_eq!(keypad.step_with(one, East), one);
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let keypad: Keypad = NUMERIC.parse().unwrap();
+        assert_eq!(keypad.shortest_paths('A', 'A'), vec![vec![]]);
+        assert_eq!(
+            keypad.shortest_paths('A', '1'),
+            vec![vec![North, West, West], vec![West, North, West]]
+        );
+        assert_eq!(
+            keypad.shortest_path('0', '9'),
+            Some(vec![North, North, North, East])
+        );
+        assert_eq!(keypad.shortest_path('0', 'X'), None);
+    }
+}
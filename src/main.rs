@@ -1,7 +1,25 @@
+mod answer;
+mod assembunny;
+mod cipher;
 mod client;
+mod counter;
+mod error;
+mod geom;
+mod grid;
+mod hashsearch;
+mod keypad;
+mod math;
+mod ocr;
+mod parse;
+mod path;
+mod search;
 
+use crate::answer::{Answer, Solution};
 use crate::client::Client;
+use crate::error::AocError;
 use clap::{Arg, ArgAction, Command};
+use serde_json::json;
+use std::process;
 use std::time::Instant;
 
 fn main() {
@@ -22,6 +40,18 @@ fn main() {
                 .long("show-time")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .default_value("text")
+                .value_parser(["text", "json"]),
+        )
+        .arg(
+            Arg::new("visualize")
+                .long("visualize")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("days")
                 .short('d') // Short flags now use single quotes
@@ -34,31 +64,105 @@ fn main() {
 
     let year: i32 = *matches.get_one::<i32>("year").expect("year is required");
     let show_time = matches.get_flag("show-time");
+    let visualize = matches.get_flag("visualize");
+    let json = matches.get_one::<String>("format").map(String::as_str) == Some("json");
     let days: Vec<i32> = matches
         .get_many::<i32>("days")
         .unwrap_or_default()
         .copied() // `get_many()` returns references, so we use `.copied()`
         .collect();
 
-    let client = Client::from_env().expect("failed to create adventofcode.com client");
+    let client = match Client::from_env() {
+        Ok(client) => client,
+        Err(err) => {
+            let err = err.context("failed to create adventofcode.com client");
+            eprintln!("error: {}", err.report());
+            process::exit(1);
+        }
+    };
     for day in days {
-        let input = client
-            .get_input(year, day)
-            .unwrap_or_else(|_| panic!("failed to get input for {} day {}", year, day));
+        let input = match client.get_input(year, day) {
+            Ok(input) => input,
+            Err(err) => {
+                let err = err.context(format!("failed to get input for {} day {}", year, day));
+                eprintln!("error: {}", err.report());
+                continue;
+            }
+        };
 
+        let (part1, part2) = match solvers(year, day) {
+            Some(solvers) => solvers,
+            None => {
+                eprintln!("error: no solution for {} day {}", year, day);
+                continue;
+            }
+        };
+        // Visualizations write to stderr so `--format json` output stays
+        // clean, and are timed because day 5 reuses the visualized answer.
         let start = Instant::now();
-        let answers = solve(year, day, &input);
+        let visualized = if visualize {
+            visualize_day(year, day, &input)
+        } else {
+            None
+        };
+        let answers = (part1(&input), visualized.map_or_else(|| part2(&input), Ok));
         let time = Instant::now() - start;
+        let secs = time.as_secs() as f64 + f64::from(time.subsec_nanos()) * 1e-9;
+
+        if json {
+            let mut out = json!({
+                "year": year,
+                "day": day,
+                "puzzle1": answer_json(&answers.0),
+                "puzzle2": answer_json(&answers.1),
+            });
+            if show_time {
+                out["time"] = json!(secs);
+            }
+            println!("{}", out);
+            continue;
+        }
 
         if show_time {
-            let time = time.as_secs() as f64 + f64::from(time.subsec_nanos()) * 1e-9;
-            println!("Day {} ({:.2}s)", day, time);
+            println!("Day {} ({:.2}s)", day, secs);
         } else {
             println!("Day {}", day);
         }
 
-        println!("  puzzle 1: {}", answers.0);
-        println!("  puzzle 2: {}", answers.1);
+        print_answer(1, answers.0);
+        print_answer(2, answers.1);
+    }
+}
+
+/// Runs the visualization for a day, returning its part 2 answer when the
+/// visualization had to compute it anyway.
+fn visualize_day(year: i32, day: i32, input: &str) -> Option<Answer> {
+    match (year, day) {
+        (2016, 5) => return Some(year2016::day05::visualize(input).into()),
+        (2016, 8) => year2016::day08::visualize(input),
+        (2016, 13) => year2016::day13::visualize(input),
+        _ => eprintln!("no visualization for {} day {}", year, day),
+    }
+    None
+}
+
+fn print_answer(part: u32, answer: Result<Answer, AocError>) {
+    match answer {
+        Ok(answer) if answer.is_multiline() => {
+            println!("  puzzle {}:", part);
+            for line in answer.normalized().lines() {
+                println!("    {}", line);
+            }
+        }
+        Ok(answer) => println!("  puzzle {}: {}", part, answer),
+        Err(err) => eprintln!("  puzzle {}: error: {}", part, err.report()),
+    }
+}
+
+fn answer_json(answer: &Result<Answer, AocError>) -> serde_json::Value {
+    match answer {
+        Ok(answer) => answer.to_json(),
+        Err(err) => json!({ "error": err.report() }),
     }
 }
 
@@ -76,20 +180,22 @@ macro_rules! advent {
             }
         )*
 
-        fn solve(year: i32, day: i32, input: &str) -> (String, String) {
+        type Solver = fn(&str) -> Result<Answer, AocError>;
+
+        fn solvers(year: i32, day: i32) -> Option<(Solver, Solver)> {
             let year_str = format!("year{}", year);
             let day_str = format!("day{:02}", day);
 
             match (year_str.as_str(), day_str.as_str()) {
                 $(
                     $(
-                        (stringify!($year), stringify!($day)) => (
-                            $year::$day::puzzle1(input).to_string(),
-                            $year::$day::puzzle2(input).to_string(),
-                        ),
+                        (stringify!($year), stringify!($day)) => Some((
+                            |input| $year::$day::puzzle1(input).into_answer(),
+                            |input| $year::$day::puzzle2(input).into_answer(),
+                        )),
                     )*
                 )*
-                _ => panic!("failed to get input for year {} day {}", year, day),
+                _ => None,
             }
         }
     };
@@ -101,4 +207,14 @@ advent!(year2016 {
     day03,
     day04,
     day05,
+    day06,
+    day07,
+    day08,
+    day09,
+    day10,
+    day11,
+    day12,
+    day13,
+    day14,
+    day15,
 },);
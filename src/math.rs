@@ -0,0 +1,77 @@
+//! Number theory helpers.
+
+use num_integer::Integer;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CrtError {
+    #[error("modulus must be positive, found {0}")]
+    InvalidModulus(i64),
+    #[error("x = {residue} (mod {modulus}) contradicts the earlier congruences")]
+    Inconsistent { residue: i64, modulus: i64 },
+    #[error("combined modulus does not fit in an i64")]
+    Overflow,
+}
+
+/// Solves the system `x = residue (mod modulus)` for every pair, returning
+/// the smallest non-negative solution and the modulus of all solutions.
+///
+/// Moduli need not be pairwise coprime; a system with shared factors is
+/// solvable when the residues agree modulo each common factor. An empty
+/// system is satisfied by every integer, giving `(0, 1)`.
+pub fn crt(congruences: &[(i64, i64)]) -> Result<(i64, i64), CrtError> {
+    let (mut x, mut m): (i128, i128) = (0, 1);
+    for &(residue, modulus) in congruences {
+        if modulus <= 0 {
+            return Err(CrtError::InvalidModulus(modulus));
+        }
+        let (a, n) = (i128::from(residue), i128::from(modulus));
+        let gcd = m.extended_gcd(&n);
+        let diff = a - x;
+        if diff % gcd.gcd != 0 {
+            return Err(CrtError::Inconsistent { residue, modulus });
+        }
+        // m * k = diff (mod n), where gcd.x inverts m / g modulo n / g.
+        let step = n / gcd.gcd;
+        let k = (diff / gcd.gcd % step * (gcd.x % step)).mod_floor(&step);
+        x += m * k;
+        m *= step;
+        if i64::try_from(m).is_err() {
+            return Err(CrtError::Overflow);
+        }
+        x = x.mod_floor(&m);
+    }
+    Ok((x as i64, m as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crt, CrtError};
+
+    #[test]
+    fn coprime() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt(&[(-1, 5), (-2, 2)]), Ok((4, 10)));
+        assert_eq!(crt(&[]), Ok((0, 1)));
+    }
+
+    #[test]
+    fn shared_factors() {
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Ok((10, 12)));
+        assert_eq!(
+            crt(&[(1, 4), (2, 6)]),
+            Err(CrtError::Inconsistent {
+                residue: 2,
+                modulus: 6
+            })
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(crt(&[(1, 0)]), Err(CrtError::InvalidModulus(0)));
+        let big = (i64::MAX / 2) | 1;
+        assert_eq!(crt(&[(0, big), (0, big - 2)]), Err(CrtError::Overflow));
+    }
+}
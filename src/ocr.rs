@@ -0,0 +1,281 @@
+//! Reads block-letter answers drawn in the standard Advent of Code fonts.
+//!
+//! Both the 6 pixel high font (mostly 4 pixels wide) and the 10 pixel high
+//! font (6 pixels wide) are supported. Letters are separated by columns
+//! without any lit pixel, so glyphs of differing widths are handled. A 6
+//! pixel high `Y` fills its whole 5 column cell and touches the next letter,
+//! so that font falls back to reading fixed cells.
+
+use crate::grid::Grid;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum OcrError {
+    #[error("unsupported glyph height {0}, expected 6 or 10")]
+    UnsupportedHeight(usize),
+    #[error("unrecognized glyph at column {column}:\n{glyph}")]
+    UnknownGlyph { column: usize, glyph: String },
+}
+
+const SMALL: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const LARGE: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+/// Renders a glyph with `#` for lit and `.` for unlit pixels.
+fn render(rows: &[Vec<bool>]) -> String {
+    rows.iter()
+        .map(|row| row.iter().map(|&p| if p { '#' } else { '.' }).collect())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Cell width of the 6 pixel high font, including the gap after a letter.
+const SMALL_PITCH: usize = 5;
+
+fn lit(rows: &[Vec<bool>], x: usize) -> bool {
+    rows.iter().any(|row| row.get(x).copied().unwrap_or(false))
+}
+
+/// Copies columns `start..end` out of every row.
+fn slice(rows: &[Vec<bool>], start: usize, end: usize) -> Vec<Vec<bool>> {
+    rows.iter()
+        .map(|row| {
+            (start..end)
+                .map(|i| row.get(i).copied().unwrap_or(false))
+                .collect()
+        })
+        .collect()
+}
+
+fn width(rows: &[Vec<bool>]) -> usize {
+    rows.iter().map(Vec::len).max().unwrap_or(0)
+}
+
+/// Splits pixel rows into glyphs at columns that have no lit pixels,
+/// returning each glyph along with its starting column.
+fn glyphs(rows: &[Vec<bool>]) -> Vec<(usize, Vec<Vec<bool>>)> {
+    let width = width(rows);
+    let mut glyphs = Vec::new();
+    let mut x = 0;
+    while x < width {
+        if !lit(rows, x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < width && lit(rows, x) {
+            x += 1;
+        }
+        glyphs.push((start, slice(rows, start, x)));
+    }
+    glyphs
+}
+
+/// Splits pixel rows into cells `pitch` columns wide, starting from column
+/// 0 as the puzzle screens lay letters out, and trims the unlit columns
+/// around each glyph.
+fn cells(rows: &[Vec<bool>], pitch: usize) -> Vec<(usize, Vec<Vec<bool>>)> {
+    let width = width(rows);
+    (0..width)
+        .step_by(pitch)
+        .filter_map(|cell| {
+            let end = (cell + pitch).min(width);
+            let start = (cell..end).find(|&x| lit(rows, x))?;
+            let end = (start..end).rfind(|&x| lit(rows, x))? + 1;
+            Some((start, slice(rows, start, end)))
+        })
+        .collect()
+}
+
+fn read(font: &[(char, &str)], glyphs: Vec<(usize, Vec<Vec<bool>>)>) -> Result<String, OcrError> {
+    glyphs
+        .into_iter()
+        .map(|(column, glyph)| {
+            let glyph = render(&glyph);
+            font.iter()
+                .find(|(_, pattern)| *pattern == glyph)
+                .map(|&(c, _)| c)
+                .ok_or(OcrError::UnknownGlyph { column, glyph })
+        })
+        .collect()
+}
+
+/// Recognizes the letters in a rectangle of pixels, given as rows.
+pub fn recognize(rows: &[Vec<bool>]) -> Result<String, OcrError> {
+    match rows.len() {
+        6 => read(SMALL, glyphs(rows)).or_else(|err| {
+            // Keep the error from splitting at gaps if fixed cells don't
+            // help either.
+            read(SMALL, cells(rows, SMALL_PITCH)).map_err(|_| err)
+        }),
+        10 => read(LARGE, glyphs(rows)),
+        h => Err(OcrError::UnsupportedHeight(h)),
+    }
+}
+
+pub fn recognize_grid(grid: &Grid<bool>) -> Result<String, OcrError> {
+    let rows: Vec<Vec<bool>> = grid.rows().map(<[bool]>::to_vec).collect();
+    recognize(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recognize, recognize_grid, OcrError};
+    use crate::grid::Grid;
+
+    fn pixels(s: &str) -> Vec<Vec<bool>> {
+        s.lines()
+            .map(|line| line.chars().map(|c| c == '#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn small_font() {
+        let screen = "\
+.##..###...##..#...#
+#..#.#..#.#..#.#...#
+#..#.###..#.....#.#.
+####.#..#.#......#..
+#..#.#..#.#..#...#..
+#..#.###...##....#..";
+        assert_eq!(recognize(&pixels(screen)).unwrap(), "ABCY");
+
+        let grid = Grid::parse_with(screen, |c| Some(c == '#')).unwrap();
+        assert_eq!(recognize_grid(&grid).unwrap(), "ABCY");
+    }
+
+    #[test]
+    fn touching_glyphs() {
+        // `Y` leaves no blank column before the next letter.
+        let screen = "\
+#...#####..##.
+#...##....#..#
+.#.#.###..#...
+..#..#....#...
+..#..#....#..#
+..#..#.....##.";
+        assert_eq!(recognize(&pixels(screen)).unwrap(), "YFC");
+
+        // A leading `I` is inset in its cell, so cells must start at column 0.
+        let screen = "\
+.###.#...#####.
+..#..#...##....
+..#...#.#.###..
+..#....#..#....
+..#....#..#....
+.###...#..#....";
+        assert_eq!(recognize(&pixels(screen)).unwrap(), "IYF");
+    }
+
+    #[test]
+    fn large_font() {
+        let screen = "\
+#....#..######
+#....#..#.....
+.#..#...#.....
+.#..#...#.....
+..##....#####.
+..##....#.....
+.#..#...#.....
+.#..#...#.....
+#....#..#.....
+#....#..######";
+        assert_eq!(recognize(&pixels(screen)).unwrap(), "XE");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            recognize(&pixels("#\n#")),
+            Err(OcrError::UnsupportedHeight(2))
+        );
+        let err = recognize(&pixels("..#\n..#\n..#\n..#\n..#\n..#")).unwrap_err();
+        assert_eq!(
+            err,
+            OcrError::UnknownGlyph {
+                column: 2,
+                glyph: "#\n#\n#\n#\n#\n#".to_owned()
+            }
+        );
+    }
+}
@@ -0,0 +1,435 @@
+//! Shared helpers for turning puzzle input into typed values.
+//!
+//! Errors carry 1-based line and column positions so a bad input line can
+//! be found without re-running the parser by hand.
+//!
+//! Line formats can also be declared with `format_struct!`, which derives
+//! `FromStr` from a pattern such as `"{name}-{id}[{checksum}]"`.
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::any::Any;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line: None,
+            column,
+            message: message.into(),
+        }
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}, column {}: {}", line, self.column, self.message),
+            None => write!(f, "column {}: {}", self.column, self.message),
+        }
+    }
+}
+
+/// Moves a `ParseError` from parsing a token that starts at `column` to
+/// its position in the whole line. Other errors give `None`.
+fn shifted<E: Any>(e: &E, column: usize) -> Option<ParseError> {
+    let inner = (e as &dyn Any).downcast_ref::<ParseError>()?;
+    Some(ParseError::new(
+        column + inner.column - 1,
+        inner.message.clone(),
+    ))
+}
+
+/// Parses `s` as a `T`, reporting failures at `column`.
+pub fn value<T>(s: &str, column: usize) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: Display + 'static,
+{
+    s.parse().map_err(|e| {
+        shifted(&e, column)
+            .unwrap_or_else(|| ParseError::new(column, format!("invalid value {:?}: {}", s, e)))
+    })
+}
+
+/// Every integer in `line`, ignoring surrounding text. A leading `-` is
+/// treated as a sign.
+pub fn ints<T>(line: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: Display + 'static,
+{
+    lazy_static! {
+        static ref INT: Regex = Regex::new(r"-?\d+").unwrap();
+    }
+    INT.find_iter(line)
+        .map(|m| value(m.as_str(), m.start() + 1))
+        .collect()
+}
+
+/// Non-empty lines of `input` with their 1-based line numbers.
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty())
+}
+
+/// Parses each non-empty line with `f`, tagging errors with the line number.
+pub fn lines_with<T, F>(input: &str, mut f: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    lines(input)
+        .map(|(n, line)| f(line).map_err(|e| e.at_line(n)))
+        .collect()
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn lines_of<T>(input: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: Display + 'static,
+{
+    lines_with(input, |line| value(line.trim(), 1))
+}
+
+/// Splits `s` on `sep`, trimming each item and skipping empty ones.
+pub fn list_of<T>(s: &str, sep: char) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: Display + 'static,
+{
+    let mut column = 1;
+    let mut out = Vec::new();
+    for item in s.split(sep) {
+        let trimmed = item.trim_start();
+        let start = column + (item.len() - trimmed.len());
+        column += item.len() + sep.len_utf8();
+        let trimmed = trimmed.trim_end();
+        if !trimmed.is_empty() {
+            out.push(value(trimmed, start)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Sections of `input` separated by blank lines.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+    lazy_static! {
+        static ref BLANK: Regex = Regex::new(r"\r?\n[ \t]*\r?\n").unwrap();
+    }
+    BLANK
+        .split(input)
+        .map(|block| block.trim_matches(|c| c == '\r' || c == '\n'))
+        .filter(|block| !block.trim().is_empty())
+}
+
+/// A regular expression that must match a whole line, with typed access to
+/// its capture groups.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    regex: Regex,
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Self {
+        let regex = Regex::new(&format!("^(?:{})$", pattern)).expect("invalid pattern");
+        Pattern { regex }
+    }
+
+    pub fn extract<'a>(&self, line: &'a str) -> Result<Fields<'a>, ParseError> {
+        match self.regex.captures(line) {
+            Some(caps) => Ok(Fields { caps }),
+            None => Err(ParseError::new(
+                1,
+                format!("{:?} does not match /{}/", line, self.regex),
+            )),
+        }
+    }
+}
+
+/// A line format with `{field}` placeholders and literal text in between.
+///
+/// Placeholders match greedily and may specify their own regex as
+/// `{field:regex}`; `{{` and `}}` stand for literal braces.
+#[derive(Clone, Debug)]
+pub struct Format {
+    source: String,
+    names: Vec<String>,
+    pattern: Pattern,
+}
+
+impl Format {
+    pub fn new(format: &str) -> Self {
+        let mut regex = String::new();
+        let mut names = Vec::new();
+        let mut chars = format.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    regex.push_str(r"\{");
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    regex.push_str(r"\}");
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    let mut depth = 0;
+                    loop {
+                        match chars.next() {
+                            Some('}') if depth == 0 => break,
+                            Some(c) => {
+                                match c {
+                                    '{' => depth += 1,
+                                    '}' => depth -= 1,
+                                    _ => {}
+                                }
+                                spec.push(c);
+                            }
+                            None => panic!("unterminated placeholder in {:?}", format),
+                        }
+                    }
+                    let (name, inner) = match spec.split_once(':') {
+                        Some((name, inner)) => (name, inner),
+                        None => (spec.as_str(), ".+"),
+                    };
+                    regex.push_str(&format!("(?P<{}>{})", name, inner));
+                    names.push(name.to_owned());
+                }
+                c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        Format {
+            source: format.to_owned(),
+            names,
+            pattern: Pattern::new(&regex),
+        }
+    }
+
+    /// Placeholder names in order of appearance.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn extract<'a>(&self, line: &'a str) -> Result<Fields<'a>, ParseError> {
+        self.pattern
+            .regex
+            .captures(line)
+            .map(|caps| Fields { caps })
+            .ok_or_else(|| {
+                ParseError::new(
+                    1,
+                    format!("{:?} does not match format {:?}", line, self.source),
+                )
+            })
+    }
+}
+
+/// Declares a struct whose `FromStr` implementation is generated from a
+/// `Format` pattern. Every field must appear as a placeholder and is parsed
+/// with its own `FromStr`.
+macro_rules! format_struct {
+    (
+        #[format = $format:literal]
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($fvis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($fvis $field: $ty),*
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = $crate::parse::ParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                ::lazy_static::lazy_static! {
+                    static ref FORMAT: $crate::parse::Format = {
+                        let format = $crate::parse::Format::new($format);
+                        let mut names: Vec<&str> = format.names().iter().map(|n| n.as_str()).collect();
+                        let mut fields = vec![$(stringify!($field)),*];
+                        names.sort_unstable();
+                        fields.sort_unstable();
+                        assert_eq!(names, fields, "format {:?} does not match fields of {}", $format, stringify!($name));
+                        format
+                    };
+                }
+                let fields = FORMAT.extract(s)?;
+                Ok($name {
+                    $($field: fields.parse_named(stringify!($field))?),*
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use format_struct;
+
+#[derive(Debug)]
+pub struct Fields<'a> {
+    caps: Captures<'a>,
+}
+
+impl<'a> Fields<'a> {
+    /// The text of capture group `i`, or an empty string if it did not
+    /// participate in the match.
+    pub fn str(&self, i: usize) -> &'a str {
+        self.caps.get(i).map_or("", |m| m.as_str())
+    }
+
+    pub fn parse<T>(&self, i: usize) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display + 'static,
+    {
+        let m = self
+            .caps
+            .get(i)
+            .ok_or_else(|| ParseError::new(1, format!("missing capture group {}", i)))?;
+        value(m.as_str(), m.start() + 1)
+    }
+
+    /// Parses the named capture group, mentioning the field and the whole
+    /// line in error messages.
+    pub fn parse_named<T>(&self, name: &str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display + 'static,
+    {
+        let line = self.str(0);
+        let m = self
+            .caps
+            .name(name)
+            .ok_or_else(|| ParseError::new(1, format!("missing field `{}` in {:?}", name, line)))?;
+        m.as_str().parse().map_err(|e| {
+            if let Some(e) = shifted(&e, m.start() + 1) {
+                return e;
+            }
+            ParseError::new(
+                m.start() + 1,
+                format!(
+                    "invalid value {:?} for `{}` in {:?}: {}",
+                    m.as_str(),
+                    name,
+                    line,
+                    e
+                ),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blocks, ints, lines_of, list_of, Format, ParseError, Pattern};
+
+    #[test]
+    fn integers() {
+        assert_eq!(ints::<i32>("x=-3, y=14 .. 7").unwrap(), vec![-3, 14, 7]);
+        assert_eq!(
+            ints::<u8>("1 300").unwrap_err(),
+            ParseError::new(
+                3,
+                "invalid value \"300\": number too large to fit in target type"
+            )
+        );
+    }
+
+    #[test]
+    fn line_numbers() {
+        assert_eq!(lines_of::<u32>("1\n\n2\n3\n").unwrap(), vec![1, 2, 3]);
+        let err = lines_of::<u32>("1\n2\nthree").unwrap_err();
+        assert_eq!((err.line, err.column), (Some(3), 1));
+        assert!(err.to_string().starts_with("line 3, column 1: "));
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(list_of::<i32>("1, 2,3 ,", ',').unwrap(), vec![1, 2, 3]);
+        assert_eq!(list_of::<i32>("1, x", ',').unwrap_err().column, 4);
+    }
+
+    #[test]
+    fn sections() {
+        let input = "a\nb\n\nc\r\n\r\nd\n";
+        assert_eq!(blocks(input).collect::<Vec<_>>(), vec!["a\nb", "c", "d"]);
+    }
+
+    #[test]
+    fn patterns() {
+        let pattern = Pattern::new(r"(\w+) is (\d+) years old");
+        let fields = pattern.extract("bob is 42 years old").unwrap();
+        assert_eq!(fields.str(1), "bob");
+        assert_eq!(fields.parse::<u32>(2).unwrap(), 42);
+        assert_eq!(fields.parse::<u8>(1).unwrap_err().column, 1);
+
+        let err = pattern.extract("bob is old").unwrap_err();
+        assert!(err.message.starts_with("\"bob is old\" does not match"));
+    }
+
+    format_struct! {
+        #[format = "#{id} @ {x},{y}: {w}x{h}"]
+        #[derive(Debug, PartialEq)]
+        struct Claim {
+            id: u32,
+            x: u32,
+            y: u32,
+            w: u32,
+            h: u32,
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let format = Format::new("{{{a:[a-z]+}}} {b:\\d{2}}-{c}");
+        assert_eq!(format.names(), &["a", "b", "c"]);
+        let fields = format.extract("{abc} 12-rest-of-line").unwrap();
+        assert_eq!(fields.parse_named::<String>("a").unwrap(), "abc");
+        assert_eq!(fields.parse_named::<u32>("b").unwrap(), 12);
+        assert_eq!(fields.parse_named::<String>("c").unwrap(), "rest-of-line");
+    }
+
+    #[test]
+    fn derived_from_str() {
+        assert_eq!(
+            "#123 @ 3,2: 5x4".parse(),
+            Ok(Claim {
+                id: 123,
+                x: 3,
+                y: 2,
+                w: 5,
+                h: 4
+            })
+        );
+        assert_eq!(
+            "#1 @ 3,2: 5x".parse::<Claim>().unwrap_err().message,
+            "\"#1 @ 3,2: 5x\" does not match format \"#{id} @ {x},{y}: {w}x{h}\""
+        );
+        let err = "#1 @ 3,-2: 5x4".parse::<Claim>().unwrap_err();
+        assert_eq!(err.column, 8);
+        assert!(err
+            .message
+            .starts_with("invalid value \"-2\" for `y` in \"#1 @ 3,-2: 5x4\""));
+    }
+}
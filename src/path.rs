@@ -0,0 +1,238 @@
+//! Records the cells a turtle walks through, for puzzles that ask where a
+//! path crosses itself.
+//!
+//! `PathTrace` stores every cell and is convenient for small walks, while
+//! `SegmentPath` only stores straight runs and scales to huge step counts.
+
+use crate::geom::{Bounds, Point, Segment, Turn, Turtle};
+use crate::grid::{Grid, Pos};
+use num::{PrimInt, Signed};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Clone, Debug)]
+pub struct PathTrace<T> {
+    turtle: Turtle<T>,
+    cells: Vec<Point<T>>,
+    visits: HashMap<Point<T>, Vec<usize>>,
+    first_revisit: Option<usize>,
+}
+
+impl<T: PrimInt + Signed + Hash> PathTrace<T> {
+    pub fn new(turtle: Turtle<T>) -> Self {
+        let mut trace = PathTrace {
+            turtle,
+            cells: Vec::new(),
+            visits: HashMap::new(),
+            first_revisit: None,
+        };
+        trace.record();
+        trace
+    }
+
+    fn record(&mut self) {
+        let step = self.cells.len();
+        let position = self.turtle.position;
+        self.cells.push(position);
+        let visits = self.visits.entry(position).or_default();
+        visits.push(step);
+        if visits.len() == 2 && self.first_revisit.is_none() {
+            self.first_revisit = Some(step);
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn) -> &mut Self {
+        self.turtle.turn(turn);
+        self
+    }
+
+    /// Walks `n` cells forward, recording every cell along the way.
+    pub fn forward(&mut self, n: usize) -> &mut Self {
+        for _ in 0..n {
+            self.turtle.step();
+            self.record();
+        }
+        self
+    }
+
+    /// Every visited cell indexed by step, starting with the initial
+    /// position at step 0.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn cells(&self) -> &[Point<T>] {
+        &self.cells
+    }
+
+    /// The step at which the path first enters a cell it has already
+    /// visited, along with that cell.
+    pub fn first_revisit(&self) -> Option<(usize, Point<T>)> {
+        self.first_revisit.map(|step| (step, self.cells[step]))
+    }
+
+    /// Cells visited more than once, ordered by when they were first
+    /// revisited.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn intersections(&self) -> Vec<Point<T>> {
+        let mut crossings: Vec<_> = self
+            .visits
+            .iter()
+            .filter(|(_, steps)| steps.len() > 1)
+            .map(|(&cell, steps)| (steps[1], cell))
+            .collect();
+        crossings.sort_unstable_by_key(|&(step, _)| step);
+        crossings.into_iter().map(|(_, cell)| cell).collect()
+    }
+
+    /// Steps at which `cell` was visited, in order.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn visits(&self, cell: Point<T>) -> &[usize] {
+        self.visits.get(&cell).map_or(&[], |steps| steps)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn visit_count(&self, cell: Point<T>) -> usize {
+        self.visits(cell).len()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn visit_counts(&self) -> impl Iterator<Item = (Point<T>, usize)> + '_ {
+        self.visits.iter().map(|(&cell, steps)| (cell, steps.len()))
+    }
+
+    /// Draws the explored area: `#` for cells visited once, `+` for cells
+    /// visited more than once, `S` for the start, `@` for the turtle and `.`
+    /// for everything else.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn render(&self) -> String {
+        let bounds = Bounds::from_points(self.cells.iter().copied()).unwrap();
+        let offset = |p: Point<T>| {
+            Pos::new(
+                (p.x - bounds.min.x).to_i64().unwrap(),
+                (p.y - bounds.min.y).to_i64().unwrap(),
+            )
+        };
+        let mut grid = Grid::new(
+            bounds.width().to_usize().unwrap(),
+            bounds.height().to_usize().unwrap(),
+            '.',
+        );
+        for (&cell, steps) in &self.visits {
+            grid[offset(cell)] = if steps.len() > 1 { '+' } else { '#' };
+        }
+        grid[offset(self.cells[0])] = 'S';
+        grid[offset(self.turtle.position)] = '@';
+        grid.to_string()
+    }
+}
+
+/// A walk stored as straight segments rather than individual cells.
+#[derive(Clone, Debug)]
+pub struct SegmentPath<T> {
+    turtle: Turtle<T>,
+    segments: Vec<Segment<T>>,
+}
+
+impl<T: PrimInt + Signed> SegmentPath<T> {
+    pub fn new(turtle: Turtle<T>) -> Self {
+        SegmentPath {
+            turtle,
+            segments: Vec::new(),
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn segments(&self) -> &[Segment<T>] {
+        &self.segments
+    }
+
+    pub fn turn(&mut self, turn: Turn) -> &mut Self {
+        self.turtle.turn(turn);
+        self
+    }
+
+    pub fn forward(&mut self, n: T) -> &mut Self {
+        let start = self.turtle.position;
+        self.turtle.forward(n);
+        if !n.is_zero() {
+            self.segments
+                .push(Segment::new(start, self.turtle.position));
+        }
+        self
+    }
+
+    /// The first cell entered for a second time, matching
+    /// `PathTrace::first_revisit` for the same walk.
+    pub fn first_revisit(&self) -> Option<Point<T>> {
+        self.segments.iter().enumerate().find_map(|(i, segment)| {
+            self.segments[..i]
+                .iter()
+                .filter_map(|earlier| segment.first_hit(earlier))
+                .min_by_key(|&hit| segment.start.manhattan(hit))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathTrace, SegmentPath};
+    use crate::geom::{Point, Turn, Turtle};
+
+    fn square_walk() -> PathTrace<i32> {
+        let mut trace = PathTrace::new(Turtle::default());
+        for &n in &[8, 4, 4, 8] {
+            trace.turn(Turn::Right).forward(n);
+        }
+        trace
+    }
+
+    #[test]
+    fn revisits() {
+        let trace = square_walk();
+        assert_eq!(trace.cells().len(), 25);
+        assert_eq!(trace.first_revisit(), Some((20, Point::new(4, 0))));
+        assert_eq!(trace.intersections(), vec![Point::new(4, 0)]);
+        assert_eq!(trace.visit_count(Point::new(4, 0)), 2);
+        assert_eq!(trace.visits(Point::new(4, 0)), &[4, 20]);
+        assert_eq!(trace.visit_count(Point::new(9, 9)), 0);
+        let mut repeated: Vec<_> = trace.visit_counts().filter(|&(_, n)| n > 1).collect();
+        repeated.sort_unstable();
+        assert_eq!(repeated, vec![(Point::new(4, 0), 2)]);
+    }
+
+    #[test]
+    fn segments() {
+        let mut path = SegmentPath::new(Turtle::default());
+        let mut trace = PathTrace::new(Turtle::default());
+        for &n in &[8, 4, 4, 0, 8] {
+            path.turn(Turn::Right).forward(n);
+            trace.turn(Turn::Right).forward(n as usize);
+        }
+        assert_eq!(path.segments().len(), 4);
+        assert_eq!(path.first_revisit(), Some(Point::new(5, 4)));
+        assert_eq!(path.first_revisit(), trace.first_revisit().map(|(_, p)| p));
+
+        let mut path = SegmentPath::new(Turtle::<i64>::default());
+        for &n in &[8_000_000_000, 4, 0, 5] {
+            path.turn(Turn::Right).forward(n);
+        }
+        assert_eq!(path.first_revisit(), Some(Point::new(8_000_000_000, 3)));
+    }
+
+    #[test]
+    fn render() {
+        let trace = square_walk();
+        assert_eq!(
+            trace.render(),
+            "\
+....@....
+....#....
+....#....
+....#....
+S###+####
+....#...#
+....#...#
+....#...#
+....#####
+"
+        );
+    }
+}
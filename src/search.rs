@@ -0,0 +1,305 @@
+//! Shortest paths over implicit state graphs, where states are generated on
+//! demand by a `neighbors` function rather than stored up front.
+//!
+//! Callers that want symmetric states to share a single visit should return
+//! them in a canonical form from `neighbors`.
+
+use crate::geom::Bounds;
+use crate::grid::Pos;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Breadth-first search for the fewest steps from `start` to any state
+/// satisfying `goal`.
+pub fn bfs<S, F, I, G>(start: S, mut neighbors: F, mut goal: G) -> Option<usize>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(start.clone());
+    queue.push_back((start, 0));
+    while let Some((state, steps)) = queue.pop_front() {
+        if goal(&state) {
+            return Some(steps);
+        }
+        for next in neighbors(&state) {
+            if seen.insert(next.clone()) {
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Breadth-first search that returns the states along a shortest path from
+/// `start` to a state satisfying `goal`, including both ends.
+pub fn bfs_path<S, F, I, G>(start: S, mut neighbors: F, mut goal: G) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut parents: HashMap<S, Option<S>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    parents.insert(start.clone(), None);
+    queue.push_back(start);
+    while let Some(state) = queue.pop_front() {
+        if goal(&state) {
+            let mut path = vec![state];
+            while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+                path.push(parent.clone());
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in neighbors(&state) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(state.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Steps to every state reachable from `start` within `max_steps`.
+pub fn bfs_distances<S, F, I>(start: S, mut neighbors: F, max_steps: usize) -> HashMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    distances.insert(start.clone(), 0);
+    queue.push_back((start, 0));
+    while let Some((state, steps)) = queue.pop_front() {
+        if steps == max_steps {
+            continue;
+        }
+        for next in neighbors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), steps + 1);
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// A grid whose cells are computed on demand by an `open` predicate instead
+/// of being stored, covering `x, y >= 0` unless given tighter bounds.
+///
+/// Searches on an unbounded grid never finish when the target is walled
+/// off, so pass bounds when that can happen.
+pub struct LazyGrid<F> {
+    open: F,
+    bounds: Option<Bounds<i64>>,
+}
+
+impl<F> LazyGrid<F>
+where
+    F: Fn(Pos) -> bool,
+{
+    pub fn new(open: F) -> Self {
+        LazyGrid { open, bounds: None }
+    }
+
+    pub fn with_bounds(mut self, bounds: Bounds<i64>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn is_open(&self, p: Pos) -> bool {
+        let inside = match &self.bounds {
+            Some(bounds) => bounds.contains(p),
+            None => p.x >= 0 && p.y >= 0,
+        };
+        inside && (self.open)(p)
+    }
+
+    pub fn neighbors(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        p.neighbors4().filter(move |&n| self.is_open(n))
+    }
+
+    pub fn distance(&self, from: Pos, to: Pos) -> Option<usize> {
+        bfs(from, |&p| self.neighbors(p), |&p| p == to)
+    }
+
+    pub fn shortest_path(&self, from: Pos, to: Pos) -> Option<Vec<Pos>> {
+        bfs_path(from, |&p| self.neighbors(p), |&p| p == to)
+    }
+
+    /// Number of open cells, including `from`, at most `steps` away.
+    pub fn reachable_within(&self, from: Pos, steps: usize) -> usize {
+        bfs_distances(from, |&p| self.neighbors(p), steps).len()
+    }
+
+    /// Draws the area around `explored` with `#` for walls, `.` for explored
+    /// cells, `O` for `path` and a space for open cells not yet explored.
+    pub fn render(&self, explored: &HashSet<Pos>, path: &[Pos]) -> String {
+        let bounds = match Bounds::from_points(explored.iter().chain(path).copied()) {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+        let on_path: HashSet<Pos> = path.iter().copied().collect();
+        let mut out = String::new();
+        for y in bounds.min.y..=bounds.max.y {
+            for x in bounds.min.x..=bounds.max.x {
+                let p = Pos::new(x, y);
+                out.push(if on_path.contains(&p) {
+                    'O'
+                } else if !self.is_open(p) {
+                    '#'
+                } else if explored.contains(&p) {
+                    '.'
+                } else {
+                    ' '
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Queue entry ordered so that `BinaryHeap` pops the lowest estimate first,
+/// preferring deeper states on ties.
+struct Entry<S> {
+    estimate: usize,
+    cost: usize,
+    state: S,
+}
+
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then(self.cost.cmp(&other.cost))
+    }
+}
+
+/// A* search for the cheapest path from `start` to a state satisfying
+/// `goal`. `neighbors` yields each next state with the cost of reaching it,
+/// and `heuristic` must never overestimate the remaining cost.
+pub fn astar<S, F, I, H, G>(
+    start: S,
+    mut neighbors: F,
+    mut heuristic: H,
+    mut goal: G,
+) -> Option<usize>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    H: FnMut(&S) -> usize,
+    G: FnMut(&S) -> bool,
+{
+    let mut best = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    best.insert(start.clone(), 0);
+    heap.push(Entry {
+        estimate: heuristic(&start),
+        cost: 0,
+        state: start,
+    });
+    while let Some(Entry { cost, state, .. }) = heap.pop() {
+        if goal(&state) {
+            return Some(cost);
+        }
+        if best.get(&state).is_some_and(|&b| b < cost) {
+            continue;
+        }
+        for (next, step) in neighbors(&state) {
+            let cost = cost + step;
+            if best.get(&next).is_some_and(|&b| b <= cost) {
+                continue;
+            }
+            best.insert(next.clone(), cost);
+            heap.push(Entry {
+                estimate: cost + heuristic(&next),
+                cost,
+                state: next,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{astar, bfs, bfs_distances, bfs_path, LazyGrid};
+    use crate::grid::Pos;
+
+    /// Steps of +1 or *2 from `n`, capped to keep the graph finite.
+    fn moves(&n: &u32) -> Vec<u32> {
+        vec![n + 1, n * 2]
+            .into_iter()
+            .filter(|&m| m <= 100)
+            .collect()
+    }
+
+    #[test]
+    fn breadth_first() {
+        assert_eq!(bfs(1, moves, |&n| n == 1), Some(0));
+        // 1 -> 2 -> 3 -> 6 -> 12 -> 24 -> 48 -> 96 -> 97
+        assert_eq!(bfs(1, moves, |&n| n == 97), Some(8));
+        assert_eq!(bfs(1, moves, |&n| n == 0), None);
+    }
+
+    #[test]
+    fn a_star() {
+        // Doubling costs 3, so going the long way can be cheaper.
+        let weighted = |&n: &u32| {
+            vec![(n + 1, 1), (n * 2, 3)]
+                .into_iter()
+                .filter(|&(m, _)| m <= 100)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(astar(1, weighted, |_| 0, |&n| n == 4), Some(3));
+        let unit = |n: &u32| moves(n).into_iter().map(|m| (m, 1));
+        assert_eq!(astar(1, unit, |_| 0, |&n| n == 97), Some(8));
+        assert_eq!(astar(1, weighted, |_| 0, |&n| n == 0), None);
+    }
+
+    #[test]
+    fn paths_and_distances() {
+        assert_eq!(bfs_path(1, moves, |&n| n == 6), Some(vec![1, 2, 3, 6]));
+        let near = bfs_distances(1, moves, 2);
+        let mut reached: Vec<u32> = near.keys().copied().collect();
+        reached.sort_unstable();
+        assert_eq!(reached, vec![1, 2, 3, 4]);
+        assert_eq!(near[&4], 2);
+    }
+
+    #[test]
+    fn lazy_grid() {
+        // Walls on every odd column except in row 0.
+        let grid = LazyGrid::new(|p: Pos| p.y == 0 || p.x % 2 == 0);
+        let (from, to) = (Pos::new(0, 3), Pos::new(2, 3));
+        assert_eq!(grid.distance(from, to), Some(8));
+        assert_eq!(grid.shortest_path(from, to).unwrap().len(), 9);
+        assert_eq!(grid.reachable_within(from, 2), 5);
+    }
+}
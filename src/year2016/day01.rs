@@ -1,168 +1,128 @@
-use std::collections::HashSet;
-
-#[derive(Debug, Default)]
-pub struct Position(pub i32, pub i32);
-
-#[derive(Debug, Copy, Clone)]
-pub enum Facing {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl Default for Facing {
-    fn default() -> Self {
-        Facing::North
-    }
-}
-
-impl Facing {
-    pub fn delta(&self) -> (i32, i32) {
-        match *self {
-            Facing::North => (0, 1),
-            Facing::South => (0, -1),
-            Facing::East => (1, 0),
-            Facing::West => (-1, 0),
-        }
-    }
-}
-
-#[derive(Debug)]
-pub struct Santa {
-    facing: Facing,
-    position: Position,
-}
-
-impl Default for Santa {
-    fn default() -> Self {
-        Self {
-            facing: Facing::default(),
-            position: Position::default(),
-        }
-    }
-}
-
-impl Santa {
-    pub fn turn(&mut self, t: Turn) {
-        self.facing = match (t, &self.facing) {
-            (Turn::Left, Facing::North) => Facing::West,
-            (Turn::Left, Facing::West) => Facing::South,
-            (Turn::Left, Facing::South) => Facing::East,
-            (Turn::Left, Facing::East) => Facing::North,
-            (Turn::Right, Facing::North) => Facing::East,
-            (Turn::Right, Facing::East) => Facing::South,
-            (Turn::Right, Facing::South) => Facing::West,
-            (Turn::Right, Facing::West) => Facing::North,
-        }
-    }
-
-    pub fn step(&mut self, n: u16) {
-        let s = i32::from(n);
-        let (dx, dy) = self.facing.delta();
-        let x = self.position.0;
-        let y = self.position.1;
-        self.position = Position(x + dx * s, y + dy * s);
-    }
-
-    pub fn walk(&mut self, instruction: Instruction) {
-        self.turn(instruction.turn);
-        self.step(instruction.steps);
-    }
-
-    pub fn distance(&self) -> u32 {
-        (self.position.0.abs() + self.position.1.abs()) as u32
+use crate::error::AocError;
+use crate::geom::{Point, Turn, Turtle};
+use crate::parse::{self, format_struct, ParseError};
+use crate::path::{PathTrace, SegmentPath};
+
+format_struct! {
+    #[format = "{turn:[LR]}{steps:[0-9]+}"]
+    #[derive(Debug)]
+    pub struct Instruction {
+        turn: Turn,
+        steps: i64,
     }
 }
 
-#[derive(Debug)]
-pub enum Turn {
-    Left,
-    Right,
+/// How to look for the first location visited twice. `CellWalk` visits
+/// every cell and is kept as a reference for `Segments`, which only
+/// compares straight runs and copes with arbitrarily long steps.
+#[derive(Clone, Copy, Debug)]
+pub enum RevisitMethod {
+    #[cfg_attr(not(test), allow(dead_code))]
+    CellWalk,
+    Segments,
 }
 
-#[derive(Debug)]
-pub struct Instruction {
-    turn: Turn,
-    steps: u16,
+fn parse_instructions(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse::list_of(input, ',')
 }
 
-fn parse_instruction(token: &str) -> Result<Instruction, &'static str> {
-    let mut item = token.trim().chars();
-    let turn = match item.next() {
-        Some('L') => Turn::Left,
-        Some('R') => Turn::Right,
-        _ => return Err("expected 'L' or 'R'"),
-    };
-    let steps: u16 = match item.as_str().parse() {
-        Ok(n) => n,
-        Err(_) => return Err("bad number"),
-    };
-    Ok(Instruction { turn, steps })
-}
-
-fn parse_instructions(input: &str) -> Result<Vec<Instruction>, &'static str> {
-    input
-        .split(',')
-        .map(|s| s.trim())
-        .filter(|&s| !s.is_empty())
-        .map(|token| parse_instruction(token))
-        .collect()
-}
-
-pub fn puzzle1(input: &str) -> u32 {
-    let mut santa = Santa::default();
-    match parse_instructions(input) {
-        Ok(instructions) => {
+pub fn first_revisit(instructions: &[Instruction], method: RevisitMethod) -> Option<Point<i64>> {
+    match method {
+        RevisitMethod::CellWalk => {
+            let mut trace = PathTrace::new(Turtle::default());
             for instr in instructions {
-                santa.walk(instr);
+                trace.turn(instr.turn).forward(instr.steps as usize);
+                if let Some((_, cell)) = trace.first_revisit() {
+                    return Some(cell);
+                }
             }
-            return santa.distance();
+            None
         }
-        Err(e) => {
-            eprint!("{:?}", e);
-            0
+        RevisitMethod::Segments => {
+            let mut path = SegmentPath::new(Turtle::default());
+            for instr in instructions {
+                path.turn(instr.turn).forward(instr.steps);
+            }
+            path.first_revisit()
         }
     }
 }
 
-pub fn puzzle2(input: &str) -> u32 {
-    let mut santa = Santa::default();
-    let mut visited: HashSet<(i32, i32)> = HashSet::new();
-    visited.insert((0, 0));
-
-    let instructions = match parse_instructions(input) {
-        Ok(v) => v,
-        Err(e) => {
-            eprint!("{:?}", e);
-            return 0;
-        }
-    };
+pub fn puzzle1(input: &str) -> Result<u64, AocError> {
+    let mut santa = Turtle::<i64>::default();
+    for instr in parse_instructions(input)? {
+        santa.turn(instr.turn).forward(instr.steps);
+    }
+    Ok(santa.distance() as u64)
+}
 
-    for instruction in instructions {
-        santa.turn(instruction.turn);
-        for _ in 0..instruction.steps {
-            santa.step(1u16);
-            let pos = (santa.position.0, santa.position.1);
-            if !visited.insert(pos) {
-                return santa.distance();
-            }
+pub fn puzzle2(input: &str) -> Result<u64, AocError> {
+    let instructions = parse_instructions(input)?;
+    let end = first_revisit(&instructions, RevisitMethod::Segments).unwrap_or_else(|| {
+        let mut santa = Turtle::default();
+        for instr in &instructions {
+            santa.turn(instr.turn).forward(instr.steps);
         }
-    }
-    santa.distance()
+        santa.position
+    });
+    Ok(end.manhattan(Point::origin()) as u64)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{first_revisit, parse_instructions, RevisitMethod};
+
     const SAMPLE_INPUT: &str = "R8, R4, R4, R8";
 
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(SAMPLE_INPUT), 8);
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), 8);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(SAMPLE_INPUT), 4);
+        assert_eq!(super::puzzle2(SAMPLE_INPUT).unwrap(), 4);
+    }
+
+    #[test]
+    fn revisit_methods_agree() {
+        for input in &[SAMPLE_INPUT, "R2, R2, R2, R4", "L2, L2, L2, R0, R0, L1", "R3"] {
+            let instructions = parse_instructions(input).unwrap();
+            assert_eq!(
+                first_revisit(&instructions, RevisitMethod::CellWalk),
+                first_revisit(&instructions, RevisitMethod::Segments),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn bad_token() {
+        let err = |input| parse_instructions(input).unwrap_err().to_string();
+        assert_eq!(
+            err("R3, L99999999999999999999"),
+            "column 6: invalid value \"99999999999999999999\" for `steps` in \
+             \"L99999999999999999999\": number too large to fit in target type"
+        );
+        assert_eq!(
+            err("R3,  X2"),
+            "column 6: \"X2\" does not match format \"{turn:[LR]}{steps:[0-9]+}\""
+        );
+    }
+
+    #[test]
+    fn huge_steps() {
+        assert_eq!(super::puzzle1("R4000000000, R4000000000").unwrap(), 8_000_000_000);
+        assert_eq!(
+            super::puzzle2("R4000000000, R1, R3000000000, R2").unwrap(),
+            1_000_000_000
+        );
+        // Both beyond `u32::MAX`.
+        assert_eq!(super::puzzle1("R5000000000, L5000000000").unwrap(), 10_000_000_000);
+        assert_eq!(
+            super::puzzle2("R6000000000, R1, R5000000000, R2").unwrap(),
+            1_000_000_000
+        );
     }
 }
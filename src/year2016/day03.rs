@@ -1,31 +1,21 @@
-use std::num::ParseIntError;
+use crate::error::AocError;
+use crate::parse::{self, ParseError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Day3Errors {
-    #[error("expected 3 numbers")]
-    NotThreeNumbers,
     #[error("expected multiple of 3")]
     NotMultipleThree,
-    #[error("parse int error")]
-    Day3ParseIntError(#[from] ParseIntError),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
 }
 
 fn parse_input(input: &str) -> Result<Vec<[u32; 3]>, Day3Errors> {
-    input
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| {
-            let nums = line
-                .split_whitespace()
-                .map(|s| s.parse::<u32>())
-                .collect::<Result<Vec<u32>, _>>()?;
-            match nums.as_slice() {
-                [a, b, c] => Ok([*a, *b, *c]),
-                _ => Err(Day3Errors::NotThreeNumbers),
-            }
-        })
-        .collect()
+    let rows = parse::lines_with(input, |line| match parse::ints::<u32>(line)?.as_slice() {
+        [a, b, c] => Ok([*a, *b, *c]),
+        _ => Err(ParseError::new(1, "expected 3 numbers")),
+    })?;
+    Ok(rows)
 }
 
 pub fn transform(rows: &[[u32; 3]]) -> Result<Vec<[u32; 3]>, Day3Errors> {
@@ -46,34 +36,50 @@ pub fn transform(rows: &[[u32; 3]]) -> Result<Vec<[u32; 3]>, Day3Errors> {
     Ok(out)
 }
 
-pub fn puzzle1(input: &str) -> u32 {
-    let rows = parse_input(input).unwrap();
-    rows.into_iter()
+impl From<Day3Errors> for AocError {
+    fn from(err: Day3Errors) -> Self {
+        match err {
+            Day3Errors::Parse(err) => AocError::Parse(err),
+            err => AocError::other(err),
+        }
+    }
+}
+
+pub fn puzzle1(input: &str) -> Result<u32, Day3Errors> {
+    let rows = parse_input(input)?;
+    Ok(rows
+        .into_iter()
         .filter(|t| {
             let mut x = *t;
             x.sort();
             x[0] + x[1] > x[2]
         })
-        .count() as u32
+        .count() as u32)
 }
 
-pub fn puzzle2(input: &str) -> u32 {
-    let rows = parse_input(input).unwrap();
-    let cols = transform(&rows).unwrap();
-    cols.into_iter().filter(|t| t[0] + t[1] > t[2]).count() as u32
+pub fn puzzle2(input: &str) -> Result<u32, Day3Errors> {
+    let rows = parse_input(input)?;
+    let cols = transform(&rows)?;
+    Ok(cols.into_iter().filter(|t| t[0] + t[1] > t[2]).count() as u32)
 }
 
 #[cfg(test)]
 mod tests {
     const SAMPLE_INPUT: &str = "5 10 25";
+    const SAMPLE_INPUT_COLUMNS: &str = "101 301 501
+102 302 502
+103 303 503
+201 401 601
+202 402 602
+203 403 603";
 
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(SAMPLE_INPUT), 0);
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), 0);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(SAMPLE_INPUT), 0);
+        assert_eq!(super::puzzle2(SAMPLE_INPUT_COLUMNS).unwrap(), 6);
     }
 }
@@ -1,142 +1,91 @@
-use std::{collections::HashMap, convert::TryInto};
-
-#[derive(Debug)]
-pub struct EncryptedData {
-    pub words: String,
-    pub sector_id: u32,
-    pub checksum: [char; 5],
-}
-
-#[derive(Debug)]
-pub enum ParseErr {
-    MissingBracket,
-    Overflow,
-    BadChecksumLength,
-    MissingMixed,
-}
-
-pub fn is_lower(s: &str) -> bool {
-    !s.is_empty() && s.bytes().all(|x| x.is_ascii_lowercase())
+use crate::cipher;
+use crate::counter::{Counter, CounterError};
+use crate::parse::{self, format_struct, ParseError};
+use std::convert::TryInto;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Checksum(pub [char; 5]);
+
+impl FromStr for Checksum {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        let checksum: [char; 5] = chars
+            .try_into()
+            .map_err(|_| "expected 5 characters")?;
+        if !checksum.iter().all(char::is_ascii_lowercase) {
+            return Err("expected lowercase letters");
+        }
+        Ok(Checksum(checksum))
+    }
 }
 
-pub fn parse_mixed(s: &str) -> Result<(u32, [char; 5]), ParseErr> {
-    let stripped_last_bracket = s.strip_suffix(']').ok_or(ParseErr::MissingBracket)?;
-    let (sector_str, checksum_str) = stripped_last_bracket
-        .split_once('[')
-        .ok_or(ParseErr::MissingBracket)?;
-    let sector_id = sector_str.parse::<u32>().map_err(|_| ParseErr::Overflow)?;
-    if checksum_str.len() != 5 {
-        return Err(ParseErr::BadChecksumLength);
+format_struct! {
+    #[format = "{words:[a-z]+(?:-[a-z]+)*}-{sector_id}[{checksum}]"]
+    #[derive(Debug)]
+    pub struct EncryptedData {
+        pub words: String,
+        pub sector_id: u32,
+        pub checksum: Checksum,
     }
-    let mut it = checksum_str.chars();
-    let checksum = [
-        it.next().unwrap(),
-        it.next().unwrap(),
-        it.next().unwrap(),
-        it.next().unwrap(),
-        it.next().unwrap(),
-    ];
-
-    Ok((sector_id, checksum))
 }
 
 impl EncryptedData {
-    pub fn from_line(line: &str) -> Result<Self, ParseErr> {
-        let mut lowers: Vec<&str> = Vec::new();
-        let mut mixed: Option<&str> = None;
-
-        for part in line.split('-') {
-            if is_lower(part) {
-                lowers.push(part);
-            } else {
-                mixed = Some(part);
-            }
-        }
-
-        let (sector_id, checksum) = parse_mixed(mixed.ok_or(ParseErr::MissingMixed)?)?;
-        let words = lowers.join(" ");
-
-        Ok(Self {
-            words,
-            sector_id,
-            checksum,
-        })
-    }
-
-    pub fn top_five(&self) -> [char; 5] {
-        let mut f: HashMap<char, u32> = HashMap::new();
-        for c in self.words.chars() {
-            if c == ' ' {
-                continue;
-            }
-            *f.entry(c).or_insert(0) += 1;
-        }
-        let mut items: Vec<(char, u32)> = f.into_iter().collect();
-        items.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
-
-        let top: Vec<char> = items.into_iter().map(|(c, _)| c).take(5).collect();
-        top.try_into().unwrap()
+    pub fn top_five(&self) -> Result<[char; 5], CounterError> {
+        self.words
+            .chars()
+            .filter(|&c| c != '-')
+            .collect::<Counter<char>>()
+            .most_common_array()
     }
 
+    /// Rooms with fewer than five distinct letters can never match.
     pub fn checksum_matches(&self) -> bool {
-        self.top_five() == self.checksum
-    }
-
-    fn shift_amount(&self) -> u8 {
-        (self.sector_id % 26) as u8
-    }
-
-    fn shift_byte(b: u8, k: u8) -> u8 {
-        if (b'a'..=b'z').contains(&b) {
-            let off = b - b'a';
-            b'a' + ((off + k) % 26)
-        } else {
-            b
-        }
+        self.top_five().is_ok_and(|top| top == self.checksum.0)
     }
 
+    /// The real room name: a Caesar shift by the sector ID, with dashes
+    /// read as spaces.
     pub fn shifted_words(&self) -> String {
-        let k = self.shift_amount();
-        let mut out = String::with_capacity(self.words.len());
-        for &b in self.words.as_bytes() {
-            out.push(Self::shift_byte(b, k) as char)
-        }
-        out
+        cipher::caesar(&self.words.replace('-', " "), self.sector_id)
     }
 }
 
-pub fn parse_input(input: &str) -> Result<Vec<EncryptedData>, ParseErr> {
-    input
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(EncryptedData::from_line)
-        .collect()
+/// Finds the sector ID of the first real room whose decrypted name
+/// contains `query`.
+pub fn find_room(rooms: &[EncryptedData], query: &str) -> Option<u32> {
+    rooms
+        .iter()
+        .filter(|room| room.checksum_matches())
+        .find(|room| room.shifted_words().contains(query))
+        .map(|room| room.sector_id)
 }
 
-pub fn puzzle1(input: &str) -> u32 {
-    parse_input(input)
-        .unwrap()
-        .into_iter()
-        .filter(|e| e.checksum_matches())
-        .fold(0, |acc, e| acc + e.sector_id)
+pub fn parse_input(input: &str) -> Result<Vec<EncryptedData>, ParseError> {
+    parse::lines_with(input, |line| line.trim().parse())
 }
 
-pub fn puzzle2(input: &str) -> u32 {
-    parse_input(input)
-        .unwrap()
+pub fn puzzle1(input: &str) -> Result<u32, ParseError> {
+    Ok(parse_input(input)?
         .into_iter()
         .filter(|e| e.checksum_matches())
-        .find_map(|e| {
-            let name = e.shifted_words();
-            let name = name.to_ascii_lowercase();
-
-            if name.contains("northpole") {
-                Some(e.sector_id)
-            } else {
-                None
-            }
-        })
-        .unwrap_or(0)
+        .fold(0, |acc, e| acc + e.sector_id))
+}
+
+pub fn puzzle2_with(input: &str, query: &str) -> Result<u32, ParseError> {
+    let rooms = parse_input(input)?;
+    Ok(find_room(&rooms, query).unwrap_or(0))
+}
+
+/// The puzzle asks for the room where North Pole objects are stored. Every
+/// real room decrypts to plain English, so letter-frequency scoring can't
+/// single it out and its name has to be matched.
+const NORTH_POLE_ROOM: &str = "northpole object";
+
+pub fn puzzle2(input: &str) -> Result<u32, ParseError> {
+    puzzle2_with(input, NORTH_POLE_ROOM)
 }
 
 #[cfg(test)]
@@ -148,11 +97,23 @@ totally-real-room-200[decoy]";
 
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(SAMPLE_INPUT), 1514);
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), 1514);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(SAMPLE_INPUT), 0);
+        assert_eq!(super::puzzle2(SAMPLE_INPUT).unwrap(), 0);
+    }
+
+    #[test]
+    fn decrypt() {
+        let rooms = super::parse_input("qzmt-zixmtkozy-ivhz-343[zimth]").unwrap();
+        assert_eq!(rooms[0].shifted_words(), "very encrypted name");
+        assert_eq!(super::find_room(&rooms, "encrypted name"), Some(343));
+        assert_eq!(super::find_room(&rooms, super::NORTH_POLE_ROOM), None);
+        assert_eq!(
+            super::puzzle2_with("qzmt-zixmtkozy-ivhz-343[zimth]", "very"),
+            Ok(343)
+        );
     }
 }
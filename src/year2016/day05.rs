@@ -1,63 +1,47 @@
-use md5::{Digest, Md5};
+use crate::hashsearch::{hex_char, leading_zeros, nibble, Digest, HashSearch};
+use colored::Colorize;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
 
 pub struct Password(pub [char; 8]);
 
-fn hex_to_char(n: u8) -> char {
-    b"0123456789abcdef"[n as usize] as char
-}
-
-fn md5_bytes(base: &str, n: u64) -> [u8; 16] {
-    let msg = format!("{base}{n}");
-    let digest = Md5::digest(msg.as_bytes());
-    digest.into()
-}
-
-fn has_five_leading_zero_hex(d: &[u8; 16]) -> bool {
-    d[0] == 0 && d[1] == 0 && (d[2] & 0xF0) == 0
-}
-
-fn sixth_hex_digit(d: &[u8; 16]) -> u8 {
-    d[2] & 0x0F
-}
-
-fn seventh_hex_digit(d: &[u8; 16]) -> u8 {
-    (d[3] & 0xF0) >> 4
+fn has_five_leading_zero_hex(d: &Digest) -> bool {
+    leading_zeros(d) >= 5
 }
 
 impl Password {
     pub fn mine(base: &str) -> Self {
         let mut out = ['\0'; 8];
-        let mut i = 0usize;
-        let mut n = 0u64;
-
-        while i < 8 {
-            let d = md5_bytes(base, n);
-            if has_five_leading_zero_hex(&d) {
-                out[i] = hex_to_char(sixth_hex_digit(&d));
-                i += 1;
-            }
-            n += 1;
+        let hits = HashSearch::new(base, has_five_leading_zero_hex);
+        for (slot, (_, d)) in out.iter_mut().zip(hits) {
+            *slot = hex_char(nibble(&d, 5));
         }
-
         Password(out)
     }
 
     pub fn mine_positional(base: &str) -> Self {
+        Self::mine_positional_with(base, |_, _| {})
+    }
+
+    /// Like `mine_positional`, but calls `progress` with the number of
+    /// nonces searched and the current slots after every batch of hashes.
+    pub fn mine_positional_with<F>(base: &str, mut progress: F) -> Self
+    where
+        F: FnMut(u64, &[Option<char>; 8]),
+    {
         let mut slots: [Option<char>; 8] = [None; 8];
         let mut filled: usize = 0;
-        let mut n: u64 = 0;
 
+        let mut search = HashSearch::new(base, has_five_leading_zero_hex);
         while filled < 8 {
-            let d = md5_bytes(base, n);
-            if has_five_leading_zero_hex(&d) {
-                let pos = sixth_hex_digit(&d) as usize;
+            for (_, d) in search.next_batch() {
+                let pos = nibble(&d, 5) as usize;
                 if pos < 8 && slots[pos].is_none() {
-                    let ch = hex_to_char(seventh_hex_digit(&d));
-                    slots[pos] = Some(ch);
+                    slots[pos] = Some(hex_char(nibble(&d, 6)));
                     filled += 1;
                 }
             }
-            n += 1;
+            progress(search.searched(), &slots);
         }
 
         let mut out = ['\0'; 8];
@@ -66,12 +50,42 @@ impl Password {
         }
         Password(out)
     }
+}
 
-    pub fn to_string(&self) -> String {
-        self.0.iter().collect()
+impl Display for Password {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|c| write!(f, "{}", c))
     }
 }
 
+/// Mines the part 2 password while redrawing it in place, filling unknown
+/// slots with random hex digits, and returns it so it isn't mined twice.
+pub fn visualize(input: &str) -> String {
+    let mut stderr = io::stderr();
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let password = Password::mine_positional_with(input.trim(), |n, slots| {
+        let mut frame = String::from("\r  decrypting: ");
+        for slot in slots {
+            match slot {
+                Some(c) => frame.push_str(&c.to_string().green().bold().to_string()),
+                None => {
+                    // xorshift, reseeded by the nonce so frames differ.
+                    seed ^= n;
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    let c = hex_char((seed & 0xf) as u8);
+                    frame.push_str(&c.to_string().red().dimmed().to_string());
+                }
+            }
+        }
+        let _ = write!(stderr, "{}", frame);
+        let _ = stderr.flush();
+    });
+    eprintln!();
+    password.to_string()
+}
+
 pub fn puzzle1(input: &str) -> String {
     Password::mine(input.trim()).to_string()
 }
@@ -0,0 +1,80 @@
+use crate::counter::{ColumnCounter, Counter};
+use crate::error::{AocError, ResultExt};
+use std::io::BufRead;
+
+/// Counts the characters in each column of a stream of messages, reading one
+/// line at a time so only the per-column tallies are kept in memory.
+pub fn count_columns<R: BufRead>(mut reader: R) -> Result<ColumnCounter<char>, AocError> {
+    let mut counter = ColumnCounter::new();
+    let mut line = String::new();
+    let mut n = 0;
+    loop {
+        line.clear();
+        n += 1;
+        if reader.read_line(&mut line).at_line(n)? == 0 {
+            break;
+        }
+        counter.add_row(line.trim_end().chars());
+    }
+    Ok(counter)
+}
+
+/// Decodes the message by picking one character from each column's counts.
+pub fn decode<R, F>(reader: R, pick: F) -> Result<String, AocError>
+where
+    R: BufRead,
+    F: Fn(&Counter<char>) -> Option<char>,
+{
+    let counter = count_columns(reader)?;
+    if counter.columns().is_empty() {
+        return Err(AocError::invalid("no messages to decode"));
+    }
+    Ok(counter
+        .columns()
+        .iter()
+        .map(|column| pick(column).expect("columns are never empty"))
+        .collect())
+}
+
+pub fn puzzle1(input: &str) -> Result<String, AocError> {
+    decode(input.as_bytes(), Counter::most_common_one)
+}
+
+pub fn puzzle2(input: &str) -> Result<String, AocError> {
+    decode(input.as_bytes(), Counter::least_common_one)
+}
+
+#[cfg(test)]
+mod tests {
+    const SAMPLE_INPUT: &str = "eedadn
+drvtee
+eandsr
+raavrd
+atevrs
+tsrnev
+sdttsa
+rasrtv
+nssdts
+ntnada
+svetve
+tesnvt
+vntsnd
+vrdear
+dvrsen
+enarar";
+
+    #[test]
+    fn puzzle1() {
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), "easter");
+    }
+
+    #[test]
+    fn puzzle2() {
+        assert_eq!(super::puzzle2(SAMPLE_INPUT).unwrap(), "advent");
+    }
+
+    #[test]
+    fn empty_input() {
+        assert!(super::puzzle1("").is_err());
+    }
+}
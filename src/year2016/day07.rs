@@ -0,0 +1,162 @@
+use crate::parse::{self, ParseError};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Supernet(&'a str),
+    Hypernet(&'a str),
+}
+
+/// Splits an address into the sequences outside and inside square
+/// brackets. Brackets must pair up and cannot nest.
+pub fn tokenize(address: &str) -> Result<Vec<Segment<'_>>, ParseError> {
+    let mut segments = Vec::new();
+    let mut open: Option<usize> = None;
+    let mut start = 0;
+    for (i, c) in address.char_indices() {
+        match (c, open) {
+            ('[', None) => {
+                if start < i {
+                    segments.push(Segment::Supernet(&address[start..i]));
+                }
+                open = Some(i);
+                start = i + 1;
+            }
+            ('[', Some(outer)) => {
+                return Err(ParseError::new(
+                    i + 1,
+                    format!("nested '[' inside bracket opened at column {}", outer + 1),
+                ))
+            }
+            (']', Some(_)) => {
+                if start < i {
+                    segments.push(Segment::Hypernet(&address[start..i]));
+                }
+                open = None;
+                start = i + 1;
+            }
+            (']', None) => return Err(ParseError::new(i + 1, "unmatched ']'")),
+            _ => {}
+        }
+    }
+    if let Some(outer) = open {
+        return Err(ParseError::new(outer + 1, "unclosed '['"));
+    }
+    if start < address.len() {
+        segments.push(Segment::Supernet(&address[start..]));
+    }
+    Ok(segments)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Support {
+    pub tls: bool,
+    pub ssl: bool,
+}
+
+/// Checks an address for TLS and SSL support, looking at each character
+/// window only once.
+pub fn analyze(segments: &[Segment]) -> Support {
+    let mut abba_outside = false;
+    let mut abba_inside = false;
+    // Both sets hold `(a, b)` for an outside `aba` or an inside `bab`, so an
+    // SSL match is the same key appearing in both.
+    let mut abas = HashSet::new();
+    let mut babs = HashSet::new();
+    let mut ssl = false;
+
+    for segment in segments {
+        let (seq, inside) = match *segment {
+            Segment::Supernet(seq) => (seq.as_bytes(), false),
+            Segment::Hypernet(seq) => (seq.as_bytes(), true),
+        };
+        for (i, w) in seq.windows(3).enumerate() {
+            if w[0] == w[2] && w[0] != w[1] {
+                if inside {
+                    babs.insert((w[1], w[0]));
+                    ssl |= abas.contains(&(w[1], w[0]));
+                } else {
+                    abas.insert((w[0], w[1]));
+                    ssl |= babs.contains(&(w[0], w[1]));
+                }
+            }
+            let abba = seq
+                .get(i + 3)
+                .is_some_and(|&d| w[0] == d && w[1] == w[2] && w[0] != w[1]);
+            if abba {
+                if inside {
+                    abba_inside = true;
+                } else {
+                    abba_outside = true;
+                }
+            }
+        }
+    }
+
+    Support {
+        tls: abba_outside && !abba_inside,
+        ssl,
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Support>, ParseError> {
+    parse::lines_with(input, |line| Ok(analyze(&tokenize(line.trim())?)))
+}
+
+pub fn puzzle1(input: &str) -> Result<usize, ParseError> {
+    Ok(parse_input(input)?.iter().filter(|s| s.tls).count())
+}
+
+pub fn puzzle2(input: &str) -> Result<usize, ParseError> {
+    Ok(parse_input(input)?.iter().filter(|s| s.ssl).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, Segment};
+
+    const SAMPLE_INPUT: &str = "abba[mnop]qrst
+abcd[bddb]xyyx
+aaaa[qwer]tyui
+ioxxoj[asdfgh]zxcvbn";
+
+    const SAMPLE_INPUT_SSL: &str = "aba[bab]xyz
+xyx[xyx]xyx
+aaa[kek]eke
+zazbz[bzb]cdb";
+
+    #[test]
+    fn puzzle1() {
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), 2);
+    }
+
+    #[test]
+    fn puzzle2() {
+        assert_eq!(super::puzzle2(SAMPLE_INPUT_SSL).unwrap(), 3);
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            tokenize("ab[cd]ef[gh]").unwrap(),
+            vec![
+                Segment::Supernet("ab"),
+                Segment::Hypernet("cd"),
+                Segment::Supernet("ef"),
+                Segment::Hypernet("gh"),
+            ]
+        );
+        assert_eq!(
+            tokenize("ab[c[d]]").unwrap_err().to_string(),
+            "column 5: nested '[' inside bracket opened at column 3"
+        );
+        assert_eq!(
+            tokenize("ab]cd").unwrap_err().to_string(),
+            "column 3: unmatched ']'"
+        );
+        assert_eq!(
+            super::puzzle1("abba\nxy[zw").unwrap_err().to_string(),
+            "line 2, column 3: unclosed '['"
+        );
+    }
+}
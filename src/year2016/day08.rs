@@ -0,0 +1,207 @@
+use crate::error::{AocError, ResultExt};
+use crate::grid::{Grid, Pos};
+use crate::ocr;
+use crate::parse::{self, ParseError, Pattern};
+use lazy_static::lazy_static;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+pub const WIDTH: usize = 50;
+pub const HEIGHT: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Rect { width: usize, height: usize },
+    RotateRow { y: usize, by: usize },
+    RotateColumn { x: usize, by: usize },
+}
+
+impl FromStr for Operation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref OPERATION: Pattern = Pattern::new(
+                r"rect (\d+)x(\d+)|rotate row y=(\d+) by (\d+)|rotate column x=(\d+) by (\d+)"
+            );
+        }
+        let fields = OPERATION.extract(s)?;
+        Ok(if !fields.str(1).is_empty() {
+            Operation::Rect {
+                width: fields.parse(1)?,
+                height: fields.parse(2)?,
+            }
+        } else if !fields.str(3).is_empty() {
+            Operation::RotateRow {
+                y: fields.parse(3)?,
+                by: fields.parse(4)?,
+            }
+        } else {
+            Operation::RotateColumn {
+                x: fields.parse(5)?,
+                by: fields.parse(6)?,
+            }
+        })
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Operation::Rect { width, height } => write!(f, "rect {}x{}", width, height),
+            Operation::RotateRow { y, by } => write!(f, "rotate row y={} by {}", y, by),
+            Operation::RotateColumn { x, by } => write!(f, "rotate column x={} by {}", x, by),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Screen {
+    pixels: Grid<bool>,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Self {
+        Screen {
+            pixels: Grid::new(width, height, false),
+        }
+    }
+
+    /// Applies an operation, rejecting ones that reach off the screen.
+    pub fn apply(&mut self, op: Operation) -> Result<(), AocError> {
+        let (width, height) = (self.pixels.width(), self.pixels.height());
+        match op {
+            Operation::Rect { width: w, height: h } if w <= width && h <= height => {
+                for y in 0..h {
+                    self.pixels.row_mut(y)[..w].fill(true);
+                }
+            }
+            Operation::RotateRow { y, by } if y < height => {
+                self.pixels.row_mut(y).rotate_right(by % width);
+            }
+            Operation::RotateColumn { x, by } if x < width => {
+                let mut column: Vec<bool> = self.pixels.column(x).copied().collect();
+                column.rotate_right(by % height);
+                for (y, lit) in column.into_iter().enumerate() {
+                    self.pixels[Pos::new(x as i64, y as i64)] = lit;
+                }
+            }
+            _ => {
+                return Err(AocError::invalid(format!(
+                    "{:?} does not fit a {}x{} screen",
+                    op.to_string(),
+                    width,
+                    height
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs every operation, calling `trace` with each operation and the
+    /// screen it produced.
+    pub fn run_with<F>(&mut self, ops: &[Operation], mut trace: F) -> Result<(), AocError>
+    where
+        F: FnMut(&Operation, &Screen),
+    {
+        for (i, &op) in ops.iter().enumerate() {
+            self.apply(op).context(format!("operation {}", i + 1))?;
+            trace(&op, self);
+        }
+        Ok(())
+    }
+
+    pub fn lit(&self) -> usize {
+        self.pixels.values().filter(|&&lit| lit).count()
+    }
+
+    pub fn letters(&self) -> Result<String, AocError> {
+        Ok(ocr::recognize_grid(&self.pixels)?)
+    }
+}
+
+impl Display for Screen {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.pixels.render(|&lit| if lit { '#' } else { '.' }))
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Operation>, ParseError> {
+    parse::lines_with(input, |line| line.trim().parse())
+}
+
+pub fn run(input: &str, width: usize, height: usize) -> Result<Screen, AocError> {
+    let mut screen = Screen::new(width, height);
+    screen.run_with(&parse_input(input)?, |_, _| {})?;
+    Ok(screen)
+}
+
+/// Prints the screen after every operation.
+pub fn visualize(input: &str) {
+    let result = parse_input(input)
+        .map_err(AocError::from)
+        .and_then(|ops| {
+            Screen::new(WIDTH, HEIGHT).run_with(&ops, |op, screen| {
+                eprintln!("{}\n{}", op, screen);
+            })
+        });
+    if let Err(err) = result {
+        eprintln!("error: {}", err.report());
+    }
+}
+
+pub fn puzzle1(input: &str) -> Result<usize, AocError> {
+    Ok(run(input, WIDTH, HEIGHT)?.lit())
+}
+
+pub fn puzzle2(input: &str) -> Result<String, AocError> {
+    run(input, WIDTH, HEIGHT)?.letters()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, Operation, Screen};
+
+    const SAMPLE_INPUT: &str = "rect 3x2
+rotate column x=1 by 1
+rotate row y=0 by 4
+rotate column x=1 by 1";
+
+    #[test]
+    fn puzzle1() {
+        let screen = run(SAMPLE_INPUT, 7, 3).unwrap();
+        assert_eq!(screen.lit(), 6);
+        assert_eq!(screen.to_string(), ".#..#.#\n#.#....\n.#.....\n");
+    }
+
+    #[test]
+    fn puzzle2() {
+        let input = "rect 4x1
+rotate column x=0 by 5
+rotate column x=1 by 5
+rotate column x=2 by 5
+rotate column x=3 by 5
+rect 1x6";
+        assert_eq!(run(input, 5, 6).unwrap().letters().unwrap(), "L");
+    }
+
+    #[test]
+    fn trace() {
+        let ops: Vec<Operation> = SAMPLE_INPUT.lines().map(|l| l.parse().unwrap()).collect();
+        let mut frames = Vec::new();
+        Screen::new(7, 3)
+            .run_with(&ops, |op, screen| frames.push(format!("{}\n{}", op, screen)))
+            .unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0], "rect 3x2\n###....\n###....\n.......\n");
+    }
+
+    #[test]
+    fn off_screen() {
+        let err = run("rect 1x1\nrect 8x1", 7, 3).unwrap_err();
+        assert_eq!(
+            err.report(),
+            "operation 2\n  caused by: \"rect 8x1\" does not fit a 7x3 screen"
+        );
+    }
+}
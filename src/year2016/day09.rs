@@ -0,0 +1,242 @@
+use crate::parse::{self, ParseError};
+use std::io::{self, Read};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Version {
+    /// Markers inside a repeated span are copied literally.
+    V1,
+    /// Markers inside a repeated span are expanded too.
+    V2,
+}
+
+/// A parsed `(AxB)` marker: repeat the next `len` bytes `times` times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Marker {
+    len: usize,
+    times: u64,
+    /// Index just past the closing parenthesis.
+    end: usize,
+}
+
+/// Parses the marker opening at `data[start]`; `offset` is the position of
+/// `data` within the whole input, used for error columns.
+fn marker(data: &[u8], start: usize, offset: usize) -> Result<Marker, ParseError> {
+    let column = offset + start + 1;
+    let close = data[start..]
+        .iter()
+        .position(|&b| b == b')')
+        .map(|i| start + i)
+        .ok_or_else(|| ParseError::new(column, "unterminated marker"))?;
+    let inner = std::str::from_utf8(&data[start + 1..close])
+        .map_err(|_| ParseError::new(column, "marker is not valid UTF-8"))?;
+    let (len, times) = inner.split_once('x').ok_or_else(|| {
+        ParseError::new(column, format!("expected (AxB) marker, found ({})", inner))
+    })?;
+    let len: usize = parse::value(len, column + 1)?;
+    let times: u64 = parse::value(times, column + len.to_string().len() + 2)?;
+    let end = close + 1;
+    if len > data.len() - end {
+        return Err(ParseError::new(
+            column,
+            format!(
+                "marker repeats {} bytes but only {} remain",
+                len,
+                data.len() - end
+            ),
+        ));
+    }
+    Ok(Marker { len, times, end })
+}
+
+fn length(data: &[u8], offset: usize, version: Version) -> Result<u64, ParseError> {
+    let mut total: u64 = 0;
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] != b'(' {
+            total += 1;
+            i += 1;
+            continue;
+        }
+        let m = marker(data, i, offset)?;
+        let span = &data[m.end..m.end + m.len];
+        let inner = match version {
+            Version::V1 => m.len as u64,
+            Version::V2 => length(span, offset + m.end, version)?,
+        };
+        let column = offset + i + 1;
+        total = inner
+            .checked_mul(m.times)
+            .and_then(|n| total.checked_add(n))
+            .ok_or_else(|| ParseError::new(column, "decompressed length overflows u64"))?;
+        i = m.end + m.len;
+    }
+    Ok(total)
+}
+
+/// Length of the decompressed text, computed without building it.
+pub fn decompressed_len(input: &str, version: Version) -> Result<u64, ParseError> {
+    length(input.trim().as_bytes(), 0, version)
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+struct Frame<'a> {
+    data: &'a [u8],
+    offset: usize,
+    pos: usize,
+    repeats: u64,
+    /// Whether markers in `data` are expanded.
+    expand: bool,
+}
+
+/// Decompresses lazily as it is read. Each nested marker keeps a frame on a
+/// stack, so memory use is bounded by the nesting depth.
+///
+/// The answers only need lengths, so this is used by the tests alone.
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Decompressor<'a> {
+    stack: Vec<Frame<'a>>,
+    version: Version,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl<'a> Decompressor<'a> {
+    pub fn new(input: &'a str, version: Version) -> Self {
+        Decompressor {
+            stack: vec![Frame {
+                data: input.trim().as_bytes(),
+                offset: 0,
+                pos: 0,
+                repeats: 1,
+                expand: true,
+            }],
+            version,
+        }
+    }
+}
+
+impl Read for Decompressor<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            let frame = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None => break,
+            };
+            if frame.pos == frame.data.len() {
+                frame.repeats -= 1;
+                if frame.repeats == 0 {
+                    self.stack.pop();
+                } else {
+                    frame.pos = 0;
+                }
+                continue;
+            }
+            let b = frame.data[frame.pos];
+            if b == b'(' && frame.expand {
+                let m = marker(frame.data, frame.pos, frame.offset)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                let child = Frame {
+                    data: &frame.data[m.end..m.end + m.len],
+                    offset: frame.offset + m.end,
+                    pos: 0,
+                    repeats: m.times,
+                    expand: self.version == Version::V2,
+                };
+                frame.pos = m.end + m.len;
+                if child.repeats > 0 && !child.data.is_empty() {
+                    self.stack.push(child);
+                }
+                continue;
+            }
+            buf[written] = b;
+            written += 1;
+            frame.pos += 1;
+        }
+        Ok(written)
+    }
+}
+
+pub fn puzzle1(input: &str) -> Result<u64, ParseError> {
+    decompressed_len(input, Version::V1)
+}
+
+pub fn puzzle2(input: &str) -> Result<u64, ParseError> {
+    decompressed_len(input, Version::V2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decompressed_len, Decompressor, Version};
+    use std::io::Read;
+
+    const SAMPLE_INPUTS_V1: &[(&str, u64)] = &[
+        ("ADVENT", 6),
+        ("A(1x5)BC", 7),
+        ("(3x3)XYZ", 9),
+        ("A(2x2)BCD(2x2)EFG", 11),
+        ("(6x1)(1x3)A", 6),
+        ("X(8x2)(3x3)ABCY", 18),
+    ];
+
+    const SAMPLE_INPUTS_V2: &[(&str, u64)] = &[
+        ("(3x3)XYZ", 9),
+        ("X(8x2)(3x3)ABCY", 20),
+        ("(27x12)(20x12)(13x14)(7x10)(1x12)A", 241920),
+        ("(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN", 445),
+    ];
+
+    fn decompress(input: &str, version: Version) -> String {
+        let mut out = String::new();
+        Decompressor::new(input, version)
+            .read_to_string(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn puzzle1() {
+        for &(input, len) in SAMPLE_INPUTS_V1 {
+            assert_eq!(super::puzzle1(input).unwrap(), len, "{}", input);
+            assert_eq!(decompress(input, Version::V1).len() as u64, len, "{}", input);
+        }
+        assert_eq!(
+            decompress("X(8x2)(3x3)ABCY", Version::V1),
+            "X(3x3)ABC(3x3)ABCY"
+        );
+    }
+
+    #[test]
+    fn puzzle2() {
+        for &(input, len) in SAMPLE_INPUTS_V2 {
+            assert_eq!(super::puzzle2(input).unwrap(), len, "{}", input);
+        }
+        assert_eq!(
+            decompress("X(8x2)(3x3)ABCY", Version::V2),
+            "XABCABCABCABCABCABCY"
+        );
+    }
+
+    #[test]
+    fn malformed_markers() {
+        let err = |input| decompressed_len(input, Version::V2).unwrap_err().to_string();
+        assert_eq!(err("AB(3x2"), "column 3: unterminated marker");
+        assert_eq!(err("(3y2)ABC"), "column 1: expected (AxB) marker, found (3y2)");
+        assert_eq!(
+            err("(12x2)AB"),
+            "column 1: marker repeats 12 bytes but only 2 remain"
+        );
+        assert_eq!(
+            err("(18446744073709551615x2)A"),
+            "column 1: marker repeats 18446744073709551615 bytes but only 1 remain"
+        );
+        assert_eq!(
+            err("(6x1)(1xz)A"),
+            "column 9: invalid value \"z\": invalid digit found in string"
+        );
+
+        let mut out = String::new();
+        assert!(Decompressor::new("A(5x2)", Version::V1)
+            .read_to_string(&mut out)
+            .is_err());
+    }
+}
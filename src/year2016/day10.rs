@@ -0,0 +1,278 @@
+use crate::error::{AocError, ResultExt};
+use crate::parse::{self, format_struct};
+use petgraph::algo;
+use petgraph::graphmap::DiGraphMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Node {
+    Bot(u32),
+    Output(u32),
+}
+
+impl FromStr for Node {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const EXPECTED: &str = "expected `bot N` or `output N`";
+        let (kind, n) = s.split_once(' ').ok_or(EXPECTED)?;
+        let n = n.parse().map_err(|_| EXPECTED)?;
+        match kind {
+            "bot" => Ok(Node::Bot(n)),
+            "output" => Ok(Node::Output(n)),
+            _ => Err(EXPECTED),
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Node::Bot(n) => write!(f, "bot {}", n),
+            Node::Output(n) => write!(f, "output {}", n),
+        }
+    }
+}
+
+format_struct! {
+    #[format = "value {value} goes to bot {bot}"]
+    #[derive(Debug)]
+    pub struct Input {
+        pub value: u32,
+        pub bot: u32,
+    }
+}
+
+format_struct! {
+    #[format = "bot {bot} gives low to {low} and high to {high}"]
+    #[derive(Debug)]
+    pub struct Rule {
+        pub bot: u32,
+        pub low: Node,
+        pub high: Node,
+    }
+}
+
+/// The bots' hand-off rules as a graph from each bot to the two nodes it
+/// gives chips to, along with the starting chips.
+#[derive(Debug)]
+pub struct Factory {
+    inputs: Vec<Input>,
+    rules: HashMap<u32, Rule>,
+    graph: DiGraphMap<Node, ()>,
+}
+
+impl FromStr for Factory {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut inputs = Vec::new();
+        let mut rules = HashMap::new();
+        let mut graph = DiGraphMap::new();
+        for (n, line) in parse::lines(input) {
+            let line = line.trim();
+            if line.starts_with("value ") {
+                inputs.push(line.parse::<Input>().at_line(n)?);
+                continue;
+            }
+            let rule = line.parse::<Rule>().at_line(n)?;
+            graph.add_edge(Node::Bot(rule.bot), rule.low, ());
+            graph.add_edge(Node::Bot(rule.bot), rule.high, ());
+            if let Some(old) = rules.insert(rule.bot, rule) {
+                let err = AocError::invalid(format!("bot {} has a second rule", old.bot));
+                return Err(err.at_line(n));
+            }
+        }
+        if let Err(cycle) = algo::toposort(&graph, None) {
+            return Err(AocError::invalid(format!(
+                "{} can pass chips back to itself",
+                cycle.node_id()
+            )));
+        }
+        Ok(Factory {
+            inputs,
+            rules,
+            graph,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Run {
+    /// Bot responsible for each `(low, high)` comparison.
+    pub comparisons: HashMap<(u32, u32), u32>,
+    pub outputs: HashMap<u32, Vec<u32>>,
+    /// Bots that never held two chips, in ascending order.
+    pub idle: Vec<u32>,
+}
+
+impl Run {
+    /// The bot that compared chips `a` and `b`, in either order.
+    pub fn comparator(&self, a: u32, b: u32) -> Option<u32> {
+        self.comparisons.get(&(a.min(b), a.max(b))).copied()
+    }
+
+    /// The single chip that ended up in output bin `n`.
+    pub fn output(&self, n: u32) -> Option<u32> {
+        match self.outputs.get(&n).map(Vec::as_slice) {
+            Some(&[chip]) => Some(chip),
+            _ => None,
+        }
+    }
+
+    fn explain_idle(&self) -> String {
+        if self.idle.is_empty() {
+            return String::new();
+        }
+        let bots: Vec<String> = self.idle.iter().map(u32::to_string).collect();
+        format!(" (bots that never fired: {})", bots.join(", "))
+    }
+}
+
+/// Chips held by each bot while the factory runs, and the bots holding two
+/// chips that are waiting to act.
+#[derive(Default)]
+struct State {
+    held: HashMap<u32, Vec<u32>>,
+    ready: VecDeque<u32>,
+    run: Run,
+}
+
+impl State {
+    fn give(&mut self, node: Node, chip: u32) -> Result<(), AocError> {
+        match node {
+            Node::Output(n) => self.run.outputs.entry(n).or_default().push(chip),
+            Node::Bot(n) => {
+                let chips = self.held.entry(n).or_default();
+                chips.push(chip);
+                match chips.len() {
+                    1 => {}
+                    2 => self.ready.push_back(n),
+                    _ => {
+                        return Err(AocError::invalid(format!(
+                            "bot {} was given a third chip",
+                            n
+                        )))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Factory {
+    /// Hands out the starting chips and lets every bot holding two chips
+    /// pass them on until no bot can act.
+    pub fn run(&self) -> Result<Run, AocError> {
+        let mut state = State::default();
+        for input in &self.inputs {
+            state.give(Node::Bot(input.bot), input.value)?;
+        }
+        let mut fired = HashSet::new();
+        while let Some(bot) = state.ready.pop_front() {
+            let rule = self.rules.get(&bot).ok_or_else(|| {
+                AocError::invalid(format!("bot {} holds two chips but has no rule", bot))
+            })?;
+            let chips = &state.held[&bot];
+            let (low, high) = (chips[0].min(chips[1]), chips[0].max(chips[1]));
+            state.run.comparisons.insert((low, high), bot);
+            fired.insert(bot);
+            state.give(rule.low, low)?;
+            state.give(rule.high, high)?;
+        }
+
+        let mut run = state.run;
+        run.idle = self
+            .graph
+            .nodes()
+            .chain(self.inputs.iter().map(|input| Node::Bot(input.bot)))
+            .filter_map(|node| match node {
+                Node::Bot(n) if !fired.contains(&n) => Some(n),
+                _ => None,
+            })
+            .collect();
+        run.idle.sort_unstable();
+        run.idle.dedup();
+        Ok(run)
+    }
+}
+
+pub fn puzzle1_with(input: &str, a: u32, b: u32) -> Result<u32, AocError> {
+    let run = input.parse::<Factory>()?.run()?;
+    run.comparator(a, b).ok_or_else(|| {
+        AocError::invalid(format!(
+            "no bot compared chips {} and {}{}",
+            a,
+            b,
+            run.explain_idle()
+        ))
+    })
+}
+
+pub fn puzzle1(input: &str) -> Result<u32, AocError> {
+    puzzle1_with(input, 61, 17)
+}
+
+pub fn puzzle2(input: &str) -> Result<u32, AocError> {
+    let run = input.parse::<Factory>()?.run()?;
+    (0..3)
+        .map(|n| {
+            run.output(n).ok_or_else(|| {
+                AocError::invalid(format!(
+                    "output {} does not hold exactly one chip{}",
+                    n,
+                    run.explain_idle()
+                ))
+            })
+        })
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Factory;
+
+    const SAMPLE_INPUT: &str = "value 5 goes to bot 2
+bot 2 gives low to bot 1 and high to bot 0
+value 3 goes to bot 1
+bot 1 gives low to output 1 and high to bot 0
+bot 0 gives low to output 2 and high to output 0
+value 2 goes to bot 2";
+
+    #[test]
+    fn puzzle1() {
+        assert_eq!(super::puzzle1_with(SAMPLE_INPUT, 5, 2).unwrap(), 2);
+        assert_eq!(super::puzzle1_with(SAMPLE_INPUT, 3, 5).unwrap(), 0);
+    }
+
+    #[test]
+    fn puzzle2() {
+        assert_eq!(super::puzzle2(SAMPLE_INPUT).unwrap(), 30);
+    }
+
+    #[test]
+    fn cycles() {
+        let err = "bot 1 gives low to bot 2 and high to output 0
+bot 2 gives low to bot 1 and high to output 1"
+            .parse::<Factory>()
+            .unwrap_err();
+        assert!(err.to_string().ends_with("can pass chips back to itself"));
+    }
+
+    #[test]
+    fn idle_bots() {
+        let input = "value 1 goes to bot 0
+value 2 goes to bot 0
+bot 0 gives low to output 0 and high to bot 1
+bot 1 gives low to output 1 and high to output 2";
+        let run = input.parse::<Factory>().unwrap().run().unwrap();
+        assert_eq!(run.idle, vec![1]);
+        assert_eq!(
+            super::puzzle1_with(input, 1, 3).unwrap_err().to_string(),
+            "no bot compared chips 1 and 3 (bots that never fired: 1)"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::error::{AocError, ResultExt};
+use crate::parse;
+use crate::search;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+const FLOORS: u8 = 4;
+const TOP: u8 = FLOORS - 1;
+const MAX_PAIRS: usize = 14;
+
+/// Floors of one element's generator and microchip.
+type Pair = (u8, u8);
+
+/// A whole facility packed into a `u64`: the elevator floor in bits 0-1,
+/// the pair count in bits 2-5, and from bit 8 four bits per pair holding
+/// the generator and microchip floors.
+///
+/// Pairs are stored sorted because swapping which element is which gives an
+/// equivalent puzzle, so states differing only by that share one encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct State(u64);
+
+impl State {
+    pub fn new(elevator: u8, pairs: &mut [Pair]) -> Self {
+        assert!(pairs.len() <= MAX_PAIRS, "too many pairs to pack");
+        pairs.sort_unstable();
+        let mut bits = elevator as u64 | (pairs.len() as u64) << 2;
+        for (i, &(generator, chip)) in pairs.iter().enumerate() {
+            bits |= ((generator as u64) << 2 | chip as u64) << (8 + 4 * i);
+        }
+        State(bits)
+    }
+
+    pub fn elevator(self) -> u8 {
+        (self.0 & 0b11) as u8
+    }
+
+    pub fn pairs(self) -> Vec<Pair> {
+        let n = (self.0 >> 2 & 0b1111) as usize;
+        (0..n)
+            .map(|i| {
+                let bits = self.0 >> (8 + 4 * i);
+                ((bits >> 2 & 0b11) as u8, (bits & 0b11) as u8)
+            })
+            .collect()
+    }
+
+    /// No microchip shares a floor with another element's generator unless
+    /// its own generator is there to shield it.
+    fn is_safe(pairs: &[Pair]) -> bool {
+        pairs.iter().all(|&(generator, chip)| {
+            generator == chip || pairs.iter().all(|&(other, _)| other != chip)
+        })
+    }
+
+    fn is_done(self) -> bool {
+        self.pairs().iter().all(|&(g, c)| g == TOP && c == TOP)
+    }
+
+    /// Lower bound on the remaining moves: every trip carries at most two
+    /// items one floor closer to the top.
+    fn estimate(self) -> usize {
+        let distance: usize = self
+            .pairs()
+            .iter()
+            .map(|&(g, c)| (2 * TOP - g - c) as usize)
+            .sum();
+        distance.div_ceil(2)
+    }
+
+    /// Safe states reachable by taking one or two items one floor up or
+    /// down in the elevator.
+    fn moves(self) -> Vec<State> {
+        let floor = self.elevator();
+        let pairs = self.pairs();
+        // Items on this floor as (pair index, is generator).
+        let items: Vec<(usize, bool)> = pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &(g, c))| {
+                let generator = Some((i, true)).filter(|_| g == floor);
+                let chip = Some((i, false)).filter(|_| c == floor);
+                generator.into_iter().chain(chip)
+            })
+            .collect();
+        let below_empty = pairs.iter().all(|&(g, c)| g >= floor && c >= floor);
+
+        let mut next = Vec::new();
+        for target in [floor.wrapping_sub(1), floor + 1] {
+            if target >= FLOORS || (target < floor && below_empty) {
+                continue;
+            }
+            for a in 0..items.len() {
+                for b in a..items.len() {
+                    let mut moved = pairs.clone();
+                    for &(i, generator) in [items[a], items[b]].iter() {
+                        if generator {
+                            moved[i].0 = target;
+                        } else {
+                            moved[i].1 = target;
+                        }
+                    }
+                    if Self::is_safe(&moved) {
+                        next.push(State::new(target, &mut moved));
+                    }
+                }
+            }
+        }
+        next
+    }
+}
+
+/// Reads the starting floor of every generator and microchip, one floor per
+/// line from the bottom.
+pub fn parse_input(input: &str) -> Result<Vec<Pair>, AocError> {
+    lazy_static! {
+        static ref ITEM: Regex =
+            Regex::new(r"(\w+)(?:-compatible)? (generator|microchip)").unwrap();
+    }
+    let mut elements: HashMap<&str, (Option<u8>, Option<u8>)> = HashMap::new();
+    for (floor, (n, line)) in parse::lines(input).enumerate() {
+        if floor >= FLOORS as usize {
+            return Err(AocError::invalid(format!("more than {} floors", FLOORS)).at_line(n));
+        }
+        for caps in ITEM.captures_iter(line) {
+            let entry = elements.entry(caps.get(1).unwrap().as_str()).or_default();
+            let slot = match &caps[2] {
+                "generator" => &mut entry.0,
+                _ => &mut entry.1,
+            };
+            if slot.replace(floor as u8).is_some() {
+                return Err(AocError::invalid(format!("duplicate {} {}", &caps[1], &caps[2])))
+                    .at_line(n);
+            }
+        }
+    }
+    let mut pairs = Vec::new();
+    for (element, items) in elements {
+        match items {
+            (Some(g), Some(c)) => pairs.push((g, c)),
+            _ => {
+                return Err(AocError::invalid(format!(
+                    "{} needs both a generator and a microchip",
+                    element
+                )))
+            }
+        }
+    }
+    if pairs.len() > MAX_PAIRS {
+        return Err(AocError::invalid(format!(
+            "at most {} elements are supported",
+            MAX_PAIRS
+        )));
+    }
+    Ok(pairs)
+}
+
+/// Fewest elevator trips to bring everything to the top floor.
+pub fn min_steps(pairs: &mut [Pair]) -> Result<usize, AocError> {
+    search::astar(
+        State::new(0, pairs),
+        |state| state.moves().into_iter().map(|s| (s, 1)),
+        |state| state.estimate(),
+        |state| state.is_done(),
+    )
+    .ok_or_else(|| AocError::invalid("everything can't be brought to the top floor"))
+}
+
+pub fn puzzle1(input: &str) -> Result<usize, AocError> {
+    min_steps(&mut parse_input(input)?)
+}
+
+pub fn puzzle2(input: &str) -> Result<usize, AocError> {
+    let mut pairs = parse_input(input)?;
+    // Elerium and dilithium generators and microchips on the first floor.
+    pairs.extend([(0, 0), (0, 0)]);
+    min_steps(&mut pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{min_steps, parse_input, State};
+
+    const SAMPLE_INPUT: &str = "\
+The first floor contains a hydrogen-compatible microchip and a lithium-compatible microchip.
+The second floor contains a hydrogen generator.
+The third floor contains a lithium generator.
+The fourth floor contains nothing relevant.";
+
+    const FIVE_PAIRS: &str = "\
+The first floor contains a thulium generator, a thulium-compatible microchip, a plutonium generator, and a strontium generator.
+The second floor contains a plutonium-compatible microchip and a strontium-compatible microchip.
+The third floor contains a promethium generator, a promethium-compatible microchip, a ruthenium generator, and a ruthenium-compatible microchip.
+The fourth floor contains nothing relevant.";
+
+    #[test]
+    fn puzzle1() {
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), 11);
+    }
+
+    #[test]
+    fn puzzle2() {
+        // Nothing to move, then a single pair carried up three floors.
+        assert_eq!(min_steps(&mut [(3, 3), (3, 3)]).unwrap(), 0);
+        assert_eq!(min_steps(&mut [(0, 0)]).unwrap(), 3);
+        // Seven pairs once the elerium and dilithium are added.
+        assert_eq!(super::puzzle1(FIVE_PAIRS).unwrap(), 31);
+        assert_eq!(super::puzzle2(FIVE_PAIRS).unwrap(), 55);
+    }
+
+    #[test]
+    fn packing() {
+        let a = State::new(2, &mut [(1, 0), (3, 2), (0, 0)]);
+        let b = State::new(2, &mut [(0, 0), (1, 0), (3, 2)]);
+        assert_eq!(a, b);
+        assert_eq!(a.elevator(), 2);
+        assert_eq!(a.pairs(), vec![(0, 0), (1, 0), (3, 2)]);
+    }
+
+    #[test]
+    fn unpaired_element() {
+        let err = parse_input("The first floor contains a hydrogen generator.").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hydrogen needs both a generator and a microchip"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use crate::assembunny::{self, Exit, Vm};
+use crate::error::AocError;
+
+/// Runs the program with register `c` preset and returns register `a`.
+pub fn run(input: &str, c: i64) -> Result<i64, AocError> {
+    let mut vm = Vm::new(assembunny::parse_program(input)?).with_register('c', c);
+    match vm.run_to_halt() {
+        Exit::Halted => Ok(vm.register('a')),
+        exit => Err(AocError::invalid(format!("program stopped with {:?}", exit))),
+    }
+}
+
+pub fn puzzle1(input: &str) -> Result<i64, AocError> {
+    run(input, 0)
+}
+
+pub fn puzzle2(input: &str) -> Result<i64, AocError> {
+    run(input, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    const SAMPLE_INPUT: &str = "cpy 41 a
+inc a
+inc a
+dec a
+jnz a 2
+dec a";
+
+    #[test]
+    fn puzzle1() {
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), 42);
+    }
+
+    #[test]
+    fn puzzle2() {
+        // Part 2 starts with register `c` set to 1.
+        assert_eq!(super::puzzle2("cpy c a\ninc a").unwrap(), 2);
+    }
+}
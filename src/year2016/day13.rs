@@ -0,0 +1,118 @@
+use crate::error::AocError;
+use crate::geom::Bounds;
+use crate::grid::Pos;
+use crate::parse;
+use crate::search::{self, LazyGrid};
+use std::collections::HashSet;
+
+const START: Pos = Pos { x: 1, y: 1 };
+const TARGET: Pos = Pos { x: 31, y: 39 };
+
+/// How far past the start and target a search may wander. Shortest routes
+/// stay much closer than this; the bound only lets a walled-off target be
+/// reported instead of searching forever.
+const MARGIN: i64 = 50;
+
+pub fn is_wall(favorite: u64, p: Pos) -> bool {
+    let (x, y) = (p.x as u64, p.y as u64);
+    let n = x * x + 3 * x + 2 * x * y + y + y * y + favorite;
+    n.count_ones() % 2 == 1
+}
+
+/// The maze, bounded to a box around the start and `target`.
+pub fn maze(favorite: u64, target: Pos) -> LazyGrid<impl Fn(Pos) -> bool> {
+    let bounds = Bounds {
+        min: Pos::new(0, 0),
+        max: Pos::new(START.x.max(target.x), START.y.max(target.y)) + Pos::new(MARGIN, MARGIN),
+    };
+    LazyGrid::new(move |p| !is_wall(favorite, p)).with_bounds(bounds)
+}
+
+fn parse_input(input: &str) -> Result<u64, AocError> {
+    Ok(parse::value(input.trim(), 1)?)
+}
+
+/// Draws the part of the maze explored while searching for `target`, with
+/// the shortest path marked.
+pub fn render(favorite: u64, target: Pos) -> Option<String> {
+    let maze = maze(favorite, target);
+    let path = maze.shortest_path(START, target)?;
+    let explored: HashSet<Pos> = search::bfs_distances(START, |&p| maze.neighbors(p), path.len() - 1)
+        .into_keys()
+        .collect();
+    Some(maze.render(&explored, &path))
+}
+
+/// Prints the explored maze with the route to the target.
+pub fn visualize(input: &str) {
+    match parse_input(input).map(|favorite| render(favorite, TARGET)) {
+        Ok(Some(map)) => eprint!("{}", map),
+        Ok(None) => eprintln!("error: the target can't be reached"),
+        Err(err) => eprintln!("error: {}", err.report()),
+    }
+}
+
+pub fn puzzle1(input: &str) -> Result<usize, AocError> {
+    maze(parse_input(input)?, TARGET)
+        .distance(START, TARGET)
+        .ok_or_else(|| AocError::invalid("the target can't be reached"))
+}
+
+pub fn puzzle2(input: &str) -> Result<usize, AocError> {
+    Ok(maze(parse_input(input)?, TARGET).reachable_within(START, 50))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_wall, maze, render, START};
+    use crate::grid::{Grid, Pos};
+
+    const SAMPLE_FAVORITE: u64 = 10;
+
+    const SAMPLE_MAZE: &str = "\
+.#.####.##
+..#..#...#
+#....##...
+###.#.###.
+.##..#..#.
+..##....#.
+#...##.###";
+
+    #[test]
+    fn walls() {
+        let expected = Grid::parse_with(SAMPLE_MAZE, |c| Some(c == '#')).unwrap();
+        for (p, &wall) in expected.iter() {
+            assert_eq!(is_wall(SAMPLE_FAVORITE, p), wall, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn puzzle1() {
+        let target = Pos::new(7, 4);
+        assert_eq!(maze(SAMPLE_FAVORITE, target).distance(START, target), Some(11));
+    }
+
+    #[test]
+    fn unreachable() {
+        // With favorite number 1 the target itself is a wall.
+        assert_eq!(
+            super::puzzle1("1").unwrap_err().to_string(),
+            "the target can't be reached"
+        );
+    }
+
+    #[test]
+    fn puzzle2() {
+        // Cells within two steps of the start: itself, (0, 1), (1, 2),
+        // (0, 0) and (2, 2).
+        let maze = maze(SAMPLE_FAVORITE, Pos::new(7, 4));
+        assert_eq!(maze.reachable_within(START, 2), 5);
+    }
+
+    #[test]
+    fn rendering() {
+        let map = render(SAMPLE_FAVORITE, Pos::new(7, 4)).unwrap();
+        assert_eq!(map.matches('O').count(), 12);
+        assert!(map.lines().all(|line| line.len() == map.lines().next().unwrap().len()));
+    }
+}
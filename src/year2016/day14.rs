@@ -0,0 +1,58 @@
+use crate::hashsearch::{first_run, has_run, md5_window};
+
+/// How far past a triple to look for the matching quintuple.
+const LOOKAHEAD: u64 = 1000;
+
+/// Indices that produce keys for `salt`, in increasing order, with every
+/// digest stretched by `rounds` extra hashes.
+pub fn key_indices(salt: &str, rounds: usize) -> impl Iterator<Item = u64> {
+    let mut window = md5_window(salt, rounds);
+    (0..).filter(move |&i| {
+        window.evict_before(i);
+        match first_run(window.get(i), 3) {
+            Some(x) => window.any(i + 1..i + 1 + LOOKAHEAD, |d| has_run(d, x, 5)),
+            None => false,
+        }
+    })
+}
+
+fn nth_key(salt: &str, rounds: usize) -> u64 {
+    key_indices(salt, rounds)
+        .nth(63)
+        .expect("key indices are unbounded")
+}
+
+pub fn puzzle1(input: &str) -> u64 {
+    nth_key(input.trim(), 0)
+}
+
+pub fn puzzle2(input: &str) -> u64 {
+    nth_key(input.trim(), 2016)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::key_indices;
+
+    const SAMPLE_INPUT: &str = "abc";
+
+    #[test]
+    fn puzzle1() {
+        let first: Vec<u64> = key_indices(SAMPLE_INPUT, 0).take(2).collect();
+        assert_eq!(first, vec![39, 92]);
+        assert_eq!(super::puzzle1(SAMPLE_INPUT), 22728);
+    }
+
+    #[test]
+    fn puzzle2() {
+        // The full sample takes millions of stretched hashes, so only check
+        // the first key here; `puzzle2_full` runs the whole thing.
+        assert_eq!(key_indices(SAMPLE_INPUT, 2016).next(), Some(10));
+    }
+
+    #[test]
+    #[ignore = "slow: hashes the full stretched sample"]
+    fn puzzle2_full() {
+        assert_eq!(super::puzzle2(SAMPLE_INPUT), 22551);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::error::AocError;
+use crate::math;
+use crate::parse::{self, format_struct, ParseError};
+
+format_struct! {
+    #[format = "Disc #{id} has {positions} positions; at time=0, it is at position {start}."]
+    #[derive(Debug, Clone, Copy)]
+    pub struct Disc {
+        pub id: i64,
+        pub positions: i64,
+        pub start: i64,
+    }
+}
+
+impl Disc {
+    /// The capsule reaches this disc `id` seconds after being dropped, so
+    /// it passes when `start + t + id = 0 (mod positions)`.
+    fn congruence(&self) -> (i64, i64) {
+        (-(self.start + self.id), self.positions)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Disc>, ParseError> {
+    parse::lines_with(input, |line| line.trim().parse())
+}
+
+/// The first time a capsule can be dropped to fall through every disc.
+pub fn first_drop(discs: &[Disc]) -> Result<i64, AocError> {
+    let congruences: Vec<(i64, i64)> = discs.iter().map(Disc::congruence).collect();
+    let (time, _) = math::crt(&congruences).map_err(AocError::other)?;
+    Ok(time)
+}
+
+pub fn puzzle1(input: &str) -> Result<i64, AocError> {
+    first_drop(&parse_input(input)?)
+}
+
+pub fn puzzle2(input: &str) -> Result<i64, AocError> {
+    let mut discs = parse_input(input)?;
+    let id = discs.iter().map(|d| d.id).max().unwrap_or(0) + 1;
+    discs.push(Disc {
+        id,
+        positions: 11,
+        start: 0,
+    });
+    first_drop(&discs)
+}
+
+#[cfg(test)]
+mod tests {
+    const SAMPLE_INPUT: &str = "\
+Disc #1 has 5 positions; at time=0, it is at position 4.
+Disc #2 has 2 positions; at time=0, it is at position 1.";
+
+    #[test]
+    fn puzzle1() {
+        assert_eq!(super::puzzle1(SAMPLE_INPUT).unwrap(), 5);
+    }
+
+    #[test]
+    fn puzzle2() {
+        assert_eq!(super::puzzle2(SAMPLE_INPUT).unwrap(), 85);
+    }
+}